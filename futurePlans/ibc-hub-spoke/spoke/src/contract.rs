@@ -0,0 +1,350 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, IbcMsg, MessageInfo, Reply, Response, StdError,
+    StdResult,
+};
+use htlc_msgs::FactoryContract;
+use ibc_interchain::packet::{SpokePacket, SwapRequest};
+
+use crate::error::ContractError;
+use crate::ibc::{
+    reply_create_escrow, reply_create_escrow_failed, reply_escrow_call_failed, send_to_hub, CREATE_ESCROW_REPLY_ID,
+    ESCROW_CALL_REPLY_ID,
+};
+use crate::msg::{ConfigResponse, EscrowInfo, ExecuteMsg, InstantiateMsg, QueryMsg, Timelocks};
+use crate::state::{Config, CONFIG, ESCROWS, REQUESTS};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    // Validate packet timeout
+    if msg.packet_timeout_seconds == 0 {
+        return Err(ContractError::InvalidPacketTimeout);
+    }
+
+    let config = Config {
+        admin: deps.api.addr_validate(&msg.admin)?,
+        htlc_factory: deps.api.addr_validate(&msg.htlc_factory)?,
+        hub_connection: msg.hub_connection,
+        hub_channel: None,
+        packet_timeout_seconds: msg.packet_timeout_seconds,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::new().add_attribute("method", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::RequestSwap {
+            swap_hash,
+            amount,
+            denom,
+            hashlock,
+            timelocks,
+            dst_chain,
+            receiver,
+            dst_amount,
+            dst_denom,
+            dst_timelocks,
+        } => {
+            let request = SwapRequest {
+                swap_hash,
+                maker: info.sender.to_string(),
+                amount,
+                denom,
+                hashlock,
+                timelocks,
+                dst_chain,
+                receiver,
+                dst_amount,
+                dst_denom,
+                dst_timelocks,
+            };
+            request_swap(deps, env, info, request)
+        }
+        ExecuteMsg::ReportStatus { swap_hash } => report_status(deps, env, swap_hash),
+        ExecuteMsg::SecretRevealed { swap_hash, secret } => {
+            secret_revealed(deps, env, info, swap_hash, secret)
+        }
+        ExecuteMsg::StatusChanged { swap_hash, .. } => status_changed(deps, env, info, swap_hash),
+        ExecuteMsg::UpdateHTLCFactory { factory } => update_htlc_factory(deps, info, factory),
+        ExecuteMsg::UpdatePacketTimeout { packet_timeout_seconds } => {
+            update_packet_timeout(deps, info, packet_timeout_seconds)
+        }
+        ExecuteMsg::UpdateAdmin { admin } => update_admin(deps, info, admin),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // The escrow's swap is parked in a transient item, cleared either way
+        CREATE_ESCROW_REPLY_ID => match msg.result.into_result() {
+            Ok(_) => reply_create_escrow(deps),
+            Err(error) => reply_create_escrow_failed(deps, error),
+        },
+        ESCROW_CALL_REPLY_ID => {
+            let error = msg.result.into_result().err().unwrap_or_default();
+            reply_escrow_call_failed(error)
+        }
+        id => Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id: {}", id)))),
+    }
+}
+
+// ================================================================================================
+// EXECUTE FUNCTIONS
+// ================================================================================================
+
+/// Forward the sender's swap request to the hub. The hub validates it; a rejection comes back
+/// in the acknowledgement and clears the request.
+fn request_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    request: SwapRequest,
+) -> Result<Response, ContractError> {
+    let swap_hash = request.swap_hash.clone();
+
+    // Validate request
+    if REQUESTS.has(deps.storage, swap_hash.clone()) || ESCROWS.has(deps.storage, swap_hash.clone()) {
+        return Err(ContractError::SwapAlreadyRequested { swap_hash });
+    }
+    if request.amount.is_zero() || request.dst_amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Invalid amount")));
+    }
+    validate_timelocks(&env, &request.timelocks)?;
+    validate_timelocks(&env, &request.dst_timelocks)?;
+
+    let msg = send_to_hub(deps.as_ref(), &env, &SpokePacket::SwapRequested(Box::new(request)))?;
+    REQUESTS.save(deps.storage, swap_hash.clone(), &info.sender)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("method", "request_swap")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("maker", info.sender))
+}
+
+/// Send the hub the factory's current status of an escrow created for it. Only changes are
+/// sent, so repeated calls can't flood the channel.
+fn report_status(deps: DepsMut, env: Env, swap_hash: String) -> Result<Response, ContractError> {
+    let msg = status_report(deps, &env, &swap_hash)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("method", "report_status")
+        .add_attribute("swap_hash", swap_hash))
+}
+
+/// Secret hook of the factory: pass secrets revealed on our escrows to the hub; the resulting
+/// status follows with `StatusChanged`. Escrows the hub didn't ask for are ignored.
+fn secret_revealed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_hash: String,
+    secret: Vec<u8>,
+) -> Result<Response, ContractError> {
+    ensure_factory(deps.as_ref(), &info)?;
+
+    if !ESCROWS.has(deps.storage, swap_hash.clone()) {
+        return Ok(Response::new()
+            .add_attribute("method", "secret_revealed")
+            .add_attribute("swap_hash", swap_hash)
+            .add_attribute("skipped", "unknown_escrow"));
+    }
+
+    let packet = SpokePacket::SecretRevealed {
+        swap_hash: swap_hash.clone(),
+        secret,
+    };
+    let msg = send_to_hub(deps.as_ref(), &env, &packet)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("method", "secret_revealed")
+        .add_attribute("swap_hash", swap_hash))
+}
+
+/// Status hook of the factory: report our escrows' status changes to the hub as they happen, so
+/// the swap moves on without anyone calling `ReportStatus`.
+fn status_changed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_hash: String,
+) -> Result<Response, ContractError> {
+    ensure_factory(deps.as_ref(), &info)?;
+
+    let res = Response::new()
+        .add_attribute("method", "status_changed")
+        .add_attribute("swap_hash", swap_hash.clone());
+    if !ESCROWS.has(deps.storage, swap_hash.clone()) {
+        return Ok(res.add_attribute("skipped", "unknown_escrow"));
+    }
+
+    match status_report(deps, &env, &swap_hash) {
+        Ok(msg) => Ok(res.add_message(msg)),
+        Err(ContractError::StatusAlreadyReported { .. }) => Ok(res.add_attribute("skipped", "already_reported")),
+        Err(err) => Err(err),
+    }
+}
+
+fn update_htlc_factory(
+    deps: DepsMut,
+    info: MessageInfo,
+    factory: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Check if sender is admin
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can update HTLC factory".to_string(),
+        });
+    }
+
+    // Validate factory address
+    config.htlc_factory = deps.api.addr_validate(&factory)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_htlc_factory")
+        .add_attribute("new_factory", factory))
+}
+
+fn update_packet_timeout(
+    deps: DepsMut,
+    info: MessageInfo,
+    packet_timeout_seconds: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Check if sender is admin
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can update the packet timeout".to_string(),
+        });
+    }
+
+    // Validate packet timeout
+    if packet_timeout_seconds == 0 {
+        return Err(ContractError::InvalidPacketTimeout);
+    }
+    config.packet_timeout_seconds = packet_timeout_seconds;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_packet_timeout")
+        .add_attribute("packet_timeout_seconds", packet_timeout_seconds.to_string()))
+}
+
+fn update_admin(deps: DepsMut, info: MessageInfo, admin: String) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Check if sender is current admin
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can update admin".to_string(),
+        });
+    }
+
+    // Validate new admin address
+    config.admin = deps.api.addr_validate(&admin)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_admin")
+        .add_attribute("new_admin", admin))
+}
+
+// ================================================================================================
+// HELPERS
+// ================================================================================================
+
+/// `EscrowStatus` packet for the factory's status of the swap's escrow, if not yet reported.
+fn status_report(
+    deps: DepsMut,
+    env: &Env,
+    swap_hash: &str,
+) -> Result<IbcMsg, ContractError> {
+    let mut escrow = ESCROWS
+        .may_load(deps.storage, swap_hash.to_string())?
+        .ok_or_else(|| ContractError::EscrowNotFound { swap_hash: swap_hash.to_string() })?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let status = FactoryContract(config.htlc_factory).htlc(&deps.querier, swap_hash)?.status;
+    if escrow.reported_status.as_ref() == Some(&status) {
+        return Err(ContractError::StatusAlreadyReported {
+            swap_hash: swap_hash.to_string(),
+            status,
+        });
+    }
+
+    let packet = SpokePacket::EscrowStatus {
+        swap_hash: swap_hash.to_string(),
+        status: status.clone(),
+    };
+    let msg = send_to_hub(deps.as_ref(), env, &packet)?;
+
+    escrow.reported_status = Some(status);
+    ESCROWS.save(deps.storage, swap_hash.to_string(), &escrow)?;
+
+    Ok(msg)
+}
+
+fn ensure_factory(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    if info.sender != CONFIG.load(deps.storage)?.htlc_factory {
+        return Err(ContractError::Unauthorized {
+            msg: "Only the HTLC factory can call its hooks".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_timelocks(env: &Env, timelocks: &Timelocks) -> Result<(), ContractError> {
+    if !timelocks.is_valid(env.block.time.seconds()) {
+        return Err(ContractError::Std(StdError::generic_err("Invalid timelocks")));
+    }
+    Ok(())
+}
+
+// ================================================================================================
+// QUERIES
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetEscrow { swap_hash } => {
+            let escrow = ESCROWS.load(deps.storage, swap_hash.clone())?;
+            to_json_binary(&EscrowInfo {
+                swap_hash,
+                mode: escrow.mode,
+                address: escrow.address,
+                reported_status: escrow.reported_status,
+            })
+        }
+        QueryMsg::GetConfig {} => {
+            let cfg = CONFIG.load(deps.storage)?;
+            to_json_binary(&ConfigResponse {
+                admin: cfg.admin,
+                htlc_factory: cfg.htlc_factory,
+                hub_connection: cfg.hub_connection,
+                hub_channel: cfg.hub_channel,
+                packet_timeout_seconds: cfg.packet_timeout_seconds,
+            })
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use cosmwasm_std::StdError;
+use htlc_msgs::types::{SwapStatus, TimelockStage};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized: {msg}")]
+    Unauthorized { msg: String },
+
+    #[error("Invalid IBC channel: {channel}")]
+    InvalidIBCChannel { channel: String },
+
+    #[error("Only unordered channels are supported")]
+    InvalidIBCOrdering,
+
+    #[error("Invalid IBC version: {version}")]
+    InvalidIBCVersion { version: String },
+
+    #[error("Channels must use the hub connection, not {connection}")]
+    InvalidConnection { connection: String },
+
+    #[error("No channel to the hub is open")]
+    HubNotConnected,
+
+    #[error("Packet timeout must be greater than zero")]
+    InvalidPacketTimeout,
+
+    #[error("Invalid packet data")]
+    InvalidPacketData,
+
+    #[error("Swap already requested: {swap_hash}")]
+    SwapAlreadyRequested { swap_hash: String },
+
+    #[error("No swap {swap_hash} was requested by {maker}")]
+    SwapRequestNotFound { swap_hash: String, maker: String },
+
+    #[error("Escrow already exists for swap {swap_hash}")]
+    EscrowAlreadyExists { swap_hash: String },
+
+    #[error("No escrow was created for swap {swap_hash}")]
+    EscrowNotFound { swap_hash: String },
+
+    #[error("Invalid escrow: {msg}")]
+    InvalidEscrow { msg: String },
+
+    #[error("Status {status:?} of swap {swap_hash} was already reported")]
+    StatusAlreadyReported { swap_hash: String, status: SwapStatus },
+
+    #[error("Escrow of swap {swap_hash} is in its {stage:?} stage; secrets are only relayed in the public withdrawal stage")]
+    RevealNotPublic { swap_hash: String, stage: TimelockStage },
+}
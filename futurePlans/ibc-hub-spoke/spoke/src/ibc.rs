@@ -0,0 +1,411 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_json, to_json_binary, Binary, Deps, DepsMut, Env, Ibc3ChannelOpenResponse,
+    IbcBasicResponse, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcChannelOpenResponse, IbcMsg, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg,
+    IbcReceiveResponse, IbcTimeout, Never, Response, SubMsg, Uint128,
+};
+use htlc_msgs::escrow;
+use htlc_msgs::factory::EscrowImmutables;
+use htlc_msgs::types::{Asset, EscrowMode, HashAlgorithm, TimelockStage, Timelocks};
+use htlc_msgs::{EscrowContract, FactoryContract};
+use ibc_interchain::packet::{
+    ack_fail, ack_success, Ack, EscrowCreated, HubPacket, SpokePacket, IBC_ORDERING, IBC_VERSION,
+};
+
+use crate::error::ContractError;
+use crate::state::{HubEscrow, CONFIG, ESCROWS, RECEIVING_SWAP, REQUESTS};
+
+/// Reply id of the factory call creating an escrow for a `CreateEscrow` packet.
+pub const CREATE_ESCROW_REPLY_ID: u64 = 1;
+
+/// Reply id of escrow calls made for `RevealSecret` and `CancelEscrow` packets.
+pub const ESCROW_CALL_REPLY_ID: u64 = 2;
+
+/// Packets not relayed within five minutes time out.
+// ================================================================================================
+// CHANNEL LIFECYCLE
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    validate_channel(deps.as_ref(), msg.channel(), msg.counterparty_version())?;
+
+    Ok(Some(Ibc3ChannelOpenResponse {
+        version: IBC_VERSION.to_string(),
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    validate_channel(deps.as_ref(), channel, msg.counterparty_version())?;
+
+    let channel_id = channel.endpoint.channel_id.clone();
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        config.hub_channel = Some(channel_id.clone());
+        Ok(config)
+    })?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ibc_channel_connect")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("connection_id", channel.connection_id.clone()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+
+    // A new handshake can replace a closed hub channel
+    let mut config = CONFIG.load(deps.storage)?;
+    if config.hub_channel.as_ref() == Some(&channel_id) {
+        config.hub_channel = None;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ibc_channel_close")
+        .add_attribute("channel_id", channel_id))
+}
+
+/// Only one channel to the hub, over the hub connection, with the shared version.
+fn validate_channel(
+    deps: Deps,
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if channel.connection_id != config.hub_connection {
+        return Err(ContractError::InvalidConnection {
+            connection: channel.connection_id.clone(),
+        });
+    }
+    if let Some(hub_channel) = config.hub_channel {
+        if hub_channel != channel.endpoint.channel_id {
+            return Err(ContractError::InvalidIBCChannel { channel: hub_channel });
+        }
+    }
+
+    if channel.order != IBC_ORDERING {
+        return Err(ContractError::InvalidIBCOrdering);
+    }
+
+    if channel.version != IBC_VERSION {
+        return Err(ContractError::InvalidIBCVersion {
+            version: channel.version.clone(),
+        });
+    }
+
+    // Only set during OpenTry/OpenAck
+    if let Some(version) = counterparty_version {
+        if version != IBC_VERSION {
+            return Err(ContractError::InvalidIBCVersion {
+                version: version.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// ================================================================================================
+// PACKET LIFECYCLE
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, Never> {
+    // Failures become error acknowledgements so the hub learns about them
+    let res = receive_packet(deps, &msg.packet.dest.channel_id, &msg.packet.data).and_then(|res| {
+        let ack = match res.data {
+            Some(ack) => ack,
+            None => ack_success(Binary::default())?,
+        };
+        Ok(IbcReceiveResponse::new()
+            .set_ack(ack)
+            .add_submessages(res.messages)
+            .add_attributes(res.attributes))
+    });
+
+    Ok(res.unwrap_or_else(|err| {
+        let error = err.to_string();
+        IbcReceiveResponse::new()
+            .set_ack(ack_fail(error.clone()).unwrap_or_default())
+            .add_attribute("method", "ibc_packet_receive")
+            .add_attribute("error", error)
+    }))
+}
+
+fn receive_packet(deps: DepsMut, channel_id: &str, data: &Binary) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.hub_channel.as_deref() != Some(channel_id) {
+        return Err(ContractError::InvalidIBCChannel { channel: channel_id.to_string() });
+    }
+
+    let packet: HubPacket = from_json(data).map_err(|_| ContractError::InvalidPacketData)?;
+    match packet {
+        HubPacket::CreateEscrow {
+            swap_hash,
+            mode,
+            maker,
+            resolver,
+            amount,
+            denom,
+            hashlock,
+            timelocks,
+        } => create_escrow(
+            deps,
+            NewEscrow { swap_hash, mode, maker, resolver, amount, denom, hashlock, timelocks },
+        ),
+        HubPacket::RevealSecret { swap_hash, secret } => reveal_secret(deps, swap_hash, secret),
+        HubPacket::CancelEscrow { swap_hash } => {
+            call_escrow(deps, swap_hash, escrow::ExecuteMsg::CancelSwap {}, "cancel_escrow")
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let packet: SpokePacket = from_json(&msg.original_packet.data)?;
+    let error = match from_json::<Ack>(&msg.acknowledgement.data)? {
+        Ack::Result(_) => None,
+        Ack::Error(error) => Some(error),
+    };
+
+    on_packet_result(deps, packet, error)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let packet: SpokePacket = from_json(&msg.packet.data)?;
+    on_packet_result(deps, packet, Some("packet timed out".to_string()))
+}
+
+// ================================================================================================
+// HUB PACKETS
+// ================================================================================================
+
+/// Escrow parameters of a `CreateEscrow` packet.
+struct NewEscrow {
+    swap_hash: String,
+    mode: EscrowMode,
+    maker: String,
+    resolver: Option<String>,
+    amount: Uint128,
+    denom: String,
+    hashlock: Vec<u8>,
+    timelocks: Timelocks,
+}
+
+/// Create the escrow of one leg through the factory. Its address is known up front, so the
+/// acknowledgement carries it; a failed creation replaces the ack with an error in the reply.
+fn create_escrow(deps: DepsMut, escrow: NewEscrow) -> Result<Response, ContractError> {
+    let NewEscrow { swap_hash, mode, maker, resolver, amount, denom, hashlock, timelocks } = escrow;
+
+    if ESCROWS.has(deps.storage, swap_hash.clone()) {
+        return Err(ContractError::EscrowAlreadyExists { swap_hash });
+    }
+
+    // The maker funds the source escrow, so it must have asked for the swap here. The request
+    // is used up once the factory created the escrow, in `reply_create_escrow`
+    if mode == EscrowMode::Source {
+        let maker_addr = deps.api.addr_validate(&maker)?;
+        if REQUESTS.may_load(deps.storage, swap_hash.clone())? != Some(maker_addr) {
+            return Err(ContractError::SwapRequestNotFound { swap_hash, maker });
+        }
+    }
+
+    // Source escrows name their resolver; a destination escrow only takes funds from the one it
+    // names, or from anyone when it names none
+    if mode == EscrowMode::Source && resolver.is_none() {
+        return Err(ContractError::InvalidEscrow {
+            msg: "Source escrows need a resolver".to_string(),
+        });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let factory = FactoryContract(config.htlc_factory);
+    let immutables = EscrowImmutables {
+        swap_hash: swap_hash.clone(),
+        mode: mode.clone(),
+        maker,
+        resolver,
+        amount,
+        asset: Asset::Native { denom },
+        hashlock,
+        hash_algorithm: HashAlgorithm::default(),
+        parts: None,
+        timelocks,
+        safety_deposit: None,
+    };
+    let msg = factory.create_escrow(immutables.clone())?;
+    let address = factory.predict_escrow_address(&deps.querier, immutables)?.address;
+    ESCROWS.save(
+        deps.storage,
+        swap_hash.clone(),
+        &HubEscrow {
+            mode: mode.clone(),
+            address: address.clone(),
+            reported_status: None,
+        },
+    )?;
+    RECEIVING_SWAP.save(deps.storage, &swap_hash)?;
+
+    let created = EscrowCreated { escrow_address: address.to_string() };
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_always(msg, CREATE_ESCROW_REPLY_ID))
+        .set_data(ack_success(to_json_binary(&created)?)?)
+        .add_attribute("method", "create_escrow")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("mode", format!("{:?}", mode))
+        .add_attribute("escrow", address))
+}
+
+/// Complete the swap's escrow with a secret revealed on the other leg. The spoke withdraws like
+/// any other account, so the hub only relays secrets once the escrow's public withdrawal stage
+/// has started; earlier reveals are refused rather than left to fail in the escrow.
+fn reveal_secret(deps: DepsMut, swap_hash: String, secret: Vec<u8>) -> Result<Response, ContractError> {
+    let escrow = ESCROWS
+        .may_load(deps.storage, swap_hash.clone())?
+        .ok_or_else(|| ContractError::EscrowNotFound { swap_hash: swap_hash.clone() })?;
+
+    let stage = EscrowContract(escrow.address).swap_info(&deps.querier)?.stage;
+    if stage != TimelockStage::PublicWithdrawal {
+        return Err(ContractError::RevealNotPublic { swap_hash, stage });
+    }
+
+    let msg = escrow::ExecuteMsg::RevealSecret { secret, merkle_proof: None };
+    call_escrow(deps, swap_hash, msg, "reveal_secret")
+}
+
+/// Execute `msg` on the swap's escrow; a failure replaces the ack with an error in the reply.
+fn call_escrow(
+    deps: DepsMut,
+    swap_hash: String,
+    msg: escrow::ExecuteMsg,
+    method: &str,
+) -> Result<Response, ContractError> {
+    let escrow = ESCROWS
+        .may_load(deps.storage, swap_hash.clone())?
+        .ok_or_else(|| ContractError::EscrowNotFound { swap_hash: swap_hash.clone() })?;
+
+    let msg = EscrowContract(escrow.address.clone()).call(msg, vec![])?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_error(msg, ESCROW_CALL_REPLY_ID))
+        .add_attribute("method", method)
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("escrow", escrow.address))
+}
+
+/// Factory created the escrow: the maker's request for it is used up
+pub fn reply_create_escrow(deps: DepsMut) -> Result<Response, ContractError> {
+    let swap_hash = RECEIVING_SWAP.load(deps.storage)?;
+    RECEIVING_SWAP.remove(deps.storage);
+    if ESCROWS.load(deps.storage, swap_hash.clone())?.mode == EscrowMode::Source {
+        REQUESTS.remove(deps.storage, swap_hash);
+    }
+
+    Ok(Response::new())
+}
+
+pub fn reply_create_escrow_failed(deps: DepsMut, error: String) -> Result<Response, ContractError> {
+    let swap_hash = RECEIVING_SWAP.load(deps.storage)?;
+    RECEIVING_SWAP.remove(deps.storage);
+    ESCROWS.remove(deps.storage, swap_hash.clone());
+
+    Ok(Response::new()
+        .set_data(ack_fail(format!("escrow creation failed: {}", error))?)
+        .add_attribute("method", "reply_create_escrow_failed")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("error", error))
+}
+
+pub fn reply_escrow_call_failed(error: String) -> Result<Response, ContractError> {
+    Ok(Response::new()
+        .set_data(ack_fail(format!("escrow call failed: {}", error))?)
+        .add_attribute("method", "reply_escrow_call_failed")
+        .add_attribute("error", error))
+}
+
+// ================================================================================================
+// SPOKE PACKET RESULTS
+// ================================================================================================
+
+/// A rejected swap request is forgotten so the maker can ask again; a lost status report can
+/// be sent again with `ReportStatus`.
+fn on_packet_result(
+    deps: DepsMut,
+    packet: SpokePacket,
+    error: Option<String>,
+) -> Result<IbcBasicResponse, ContractError> {
+    let (swap_hash, action) = match &packet {
+        SpokePacket::SwapRequested(request) => (request.swap_hash.clone(), "swap_requested"),
+        SpokePacket::EscrowStatus { swap_hash, .. } => (swap_hash.clone(), "escrow_status"),
+        SpokePacket::SecretRevealed { swap_hash, .. } => (swap_hash.clone(), "secret_revealed"),
+    };
+
+    if error.is_some() {
+        match packet {
+            SpokePacket::SwapRequested(_) => REQUESTS.remove(deps.storage, swap_hash.clone()),
+            SpokePacket::EscrowStatus { .. } => {
+                if let Some(mut escrow) = ESCROWS.may_load(deps.storage, swap_hash.clone())? {
+                    escrow.reported_status = None;
+                    ESCROWS.save(deps.storage, swap_hash.clone(), &escrow)?;
+                }
+            }
+            SpokePacket::SecretRevealed { .. } => {}
+        }
+    }
+
+    let mut res = IbcBasicResponse::new()
+        .add_attribute("method", "ibc_packet_result")
+        .add_attribute("action", action)
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("success", error.is_none().to_string());
+    if let Some(error) = error {
+        res = res.add_attribute("error", error);
+    }
+    Ok(res)
+}
+
+// ================================================================================================
+// HELPERS
+// ================================================================================================
+
+/// Packet for the hub over the channel opened with it.
+pub(crate) fn send_to_hub(deps: Deps, env: &Env, packet: &SpokePacket) -> Result<IbcMsg, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let channel_id = config.hub_channel.ok_or(ContractError::HubNotConnected)?;
+
+    Ok(IbcMsg::SendPacket {
+        channel_id,
+        data: to_json_binary(packet)?,
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(config.packet_timeout_seconds)),
+    })
+}
@@ -0,0 +1,8 @@
+pub mod contract;
+pub mod error;
+pub mod ibc;
+pub mod msg;
+pub mod state;
+
+pub use contract::{execute, instantiate, query};
+pub use error::ContractError;
@@ -0,0 +1,91 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Uint128};
+use htlc_msgs::types::{EscrowMode, SwapStatus};
+
+pub use htlc_msgs::types::Timelocks;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub admin: String,
+    pub htlc_factory: String,
+    /// Connection to the hub chain; channels over any other connection are refused
+    pub hub_connection: String,
+    /// Lifetime of packets sent to the hub
+    pub packet_timeout_seconds: u64,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    // Ask the hub for a swap with the sender as maker; once a resolver takes it the hub has
+    // this spoke create the source escrow, which the maker then funds
+    RequestSwap {
+        swap_hash: String,
+        amount: Uint128,
+        denom: String,
+        hashlock: Vec<u8>,
+        timelocks: Timelocks,
+        dst_chain: String,
+        receiver: String,
+        dst_amount: Uint128,
+        dst_denom: String,
+        dst_timelocks: Timelocks,
+    },
+
+    // Report the current status of an escrow created for the hub; anyone may call it. Changes
+    // are reported through the factory's hook, so this only resends a lost report
+    ReportStatus {
+        swap_hash: String,
+    },
+
+    // Secret hook of the escrow factory (`SecretHookMsg`)
+    SecretRevealed {
+        swap_hash: String,
+        secret: Vec<u8>,
+    },
+
+    StatusChanged {
+        swap_hash: String,
+        status: SwapStatus,
+    },
+
+    // Admin functions
+    UpdateHTLCFactory {
+        factory: String,
+    },
+
+    UpdatePacketTimeout {
+        packet_timeout_seconds: u64,
+    },
+
+    UpdateAdmin {
+        admin: String,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(EscrowInfo)]
+    GetEscrow { swap_hash: String },
+
+    #[returns(ConfigResponse)]
+    GetConfig {},
+}
+
+#[cw_serde]
+pub struct EscrowInfo {
+    pub swap_hash: String,
+    pub mode: EscrowMode,
+    pub address: Addr,
+    /// Status last reported to the hub
+    pub reported_status: Option<SwapStatus>,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub admin: Addr,
+    pub htlc_factory: Addr,
+    pub hub_connection: String,
+    pub hub_channel: Option<String>,
+    pub packet_timeout_seconds: u64,
+}
@@ -0,0 +1,34 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+use htlc_msgs::types::{EscrowMode, SwapStatus};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub admin: Addr,
+    pub htlc_factory: Addr,
+    pub hub_connection: String,
+    /// Set once the channel handshake with the hub completes
+    pub hub_channel: Option<String>,
+    /// Packets to the hub not relayed within this time out
+    pub packet_timeout_seconds: u64,
+}
+
+/// Escrow created through the factory on behalf of the hub.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HubEscrow {
+    pub mode: EscrowMode,
+    pub address: Addr,
+    pub reported_status: Option<SwapStatus>,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+// swap_hash -> maker of a swap requested from the hub and not yet given an escrow
+pub const REQUESTS: Map<String, Addr> = Map::new("requests");
+
+pub const ESCROWS: Map<String, HubEscrow> = Map::new("escrows");
+
+// Swap whose escrow is being created while a hub packet is received, for the failure reply
+pub const RECEIVING_SWAP: Item<String> = Item::new("receiving_swap");
@@ -1,88 +1,354 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmMsg,
+    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
 };
+use cw_storage_plus::Bound;
+use htlc_msgs::types::EscrowMode;
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, CONFIG, ROUTES};
-use cosmwasm_std::{IbcMsg, IbcTimeout};
+use crate::ibc::{reveal_to_leg, send_to_spoke};
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, LegStatus, QueryMsg, RouteInfo, SwapInfo,
+    SwapStatus,
+};
+use crate::packet::HubPacket;
+use crate::state::{status_key, Config, CHANNEL_CHAINS, CONFIG, RESOLVERS, ROUTES, SWAPS};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    // Validate packet timeout
+    if msg.packet_timeout_seconds == 0 {
+        return Err(ContractError::InvalidPacketTimeout);
+    }
+
     let config = Config {
         admin: deps.api.addr_validate(&msg.admin)?,
-        htlc_factory: deps.api.addr_validate(&msg.htlc_factory)?,
-        ibc_channel: msg.ibc_channel,
+        packet_timeout_seconds: msg.packet_timeout_seconds,
     };
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new().add_attribute("method", "instantiate"))
 }
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::CreateOrder { swap_hash, maker, amount, denom, hashlock, timelock, .. } => {
-            let config = CONFIG.load(deps.storage)?;
-            let msg = to_json_binary(&serde_json::json!({
-                "create_h_t_l_c": {
-                    "swap_hash": swap_hash,
-                    "maker": maker,
-                    "amount": amount,
-                    "denom": denom,
-                    "hashlock": hashlock,
-                    "timelock": timelock
-                }
-            }))?;
-            let exec = WasmMsg::Execute { contract_addr: config.htlc_factory.to_string(), msg, funds: vec![] };
-            Ok(Response::new().add_message(exec).add_attribute("method", "CreateOrder"))
+        ExecuteMsg::AcceptSwap { swap_hash, src_resolver, dst_resolver } => {
+            accept_swap(deps, env, info, swap_hash, src_resolver, dst_resolver)
         }
-        ExecuteMsg::SetRoute { chain, channel_id } => {
-            ROUTES.save(deps.storage, chain.clone(), &channel_id)?;
-            Ok(Response::new().add_attribute("action", "set_route").add_attribute("chain", chain).add_attribute("channel", channel_id))
+        ExecuteMsg::RefundSwap { swap_hash } => refund_swap(deps, env, swap_hash),
+        ExecuteMsg::RelaySecret { swap_hash } => relay_secret(deps, env, swap_hash),
+        ExecuteMsg::SetRoute { chain, channel_id } => set_route(deps, info, chain, channel_id),
+        ExecuteMsg::UpdateResolvers { add, remove } => update_resolvers(deps, info, add, remove),
+        ExecuteMsg::UpdatePacketTimeout { packet_timeout_seconds } => {
+            update_packet_timeout(deps, info, packet_timeout_seconds)
         }
-        ExecuteMsg::SendCreateHTLC { swap_hash, maker, amount, denom, hashlock, timelock, dest_chain } => {
-            let channel = ROUTES.may_load(deps.storage, dest_chain.clone())?
-                .ok_or_else(|| ContractError::InvalidIBCChannel { channel: dest_chain.clone() })?;
-
-            let packet = serde_json::to_vec(&crate::ibc::IbcPacket {
-                action: crate::ibc::IbcAction::CreateHTLC { swap_hash: swap_hash.clone(), maker: maker.clone(), amount: amount.clone(), denom: denom.clone(), hashlock: hashlock.clone(), timelock },
-            }).map_err(|e| cosmwasm_std::StdError::generic_err(e.to_string()))?;
-
-            // Simple time-based timeout: 5 minutes from now
-            let ibc_msg = IbcMsg::SendPacket {
-                channel_id: channel.clone(),
-                data: packet.into(),
-                timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(300)),
-            };
-
-            Ok(Response::new()
-                .add_message(CosmosMsg::Ibc(ibc_msg))
-                .add_attribute("action", "send_create_htlc")
-                .add_attribute("channel", channel)
-                .add_attribute("swap_hash", swap_hash))
+        ExecuteMsg::UpdateAdmin { admin } => update_admin(deps, info, admin),
+    }
+}
+
+// ================================================================================================
+// SWAPS
+// ================================================================================================
+
+/// Match an open swap with the sending resolver and have the source spoke create the maker's
+/// escrow. The destination escrow follows once the maker has funded it.
+fn accept_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_hash: String,
+    src_resolver: String,
+    dst_resolver: String,
+) -> Result<Response, ContractError> {
+    // Validate resolver
+    if !RESOLVERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {
+            msg: "Only registered resolvers can accept swaps".to_string(),
+        });
+    }
+    if src_resolver.is_empty() {
+        return Err(ContractError::InvalidSwap {
+            msg: "src_resolver is required".to_string(),
+        });
+    }
+    if dst_resolver.is_empty() {
+        return Err(ContractError::InvalidSwap {
+            msg: "dst_resolver is required".to_string(),
+        });
+    }
+
+    let mut swap = SWAPS
+        .may_load(deps.storage, swap_hash.clone())?
+        .ok_or_else(|| ContractError::SwapNotFound { swap_hash: swap_hash.clone() })?;
+
+    if swap.status != SwapStatus::Open {
+        return Err(ContractError::InvalidSwapStatus { swap_hash, status: swap.status });
+    }
+
+    // Both escrows must still be creatable
+    let now = env.block.time.seconds();
+    if !swap.src.timelocks.is_valid(now) || !swap.dst.timelocks.is_valid(now) {
+        return Err(ContractError::InvalidSwap {
+            msg: "Swap has expired".to_string(),
+        });
+    }
+
+    let packet = HubPacket::CreateEscrow {
+        swap_hash: swap_hash.clone(),
+        mode: EscrowMode::Source,
+        maker: swap.src.maker.clone(),
+        resolver: Some(src_resolver.clone()),
+        amount: swap.src.amount,
+        denom: swap.src.denom.clone(),
+        hashlock: swap.hashlock.clone(),
+        timelocks: swap.src.timelocks.clone(),
+    };
+    let msg = send_to_spoke(deps.as_ref(), &env, &swap.src.chain, &packet)?;
+
+    swap.src.resolver = Some(src_resolver);
+    swap.dst.resolver = Some(dst_resolver);
+    swap.src.status = LegStatus::Requested;
+    swap.resolver = Some(info.sender.clone());
+    swap.status = SwapStatus::Matched;
+    SWAPS.save(deps.storage, swap_hash.clone(), &swap)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("method", "accept_swap")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("resolver", info.sender))
+}
+
+/// Cancel every funded escrow of the swap in its public cancellation stage; spokes cancel like
+/// any other account, so earlier stages are reserved to the resolver. Once either leg expires
+/// the swap is refunding; the source leg usually expires later and is cancelled by a second
+/// call.
+fn refund_swap(deps: DepsMut, env: Env, swap_hash: String) -> Result<Response, ContractError> {
+    let mut swap = SWAPS
+        .may_load(deps.storage, swap_hash.clone())?
+        .ok_or_else(|| ContractError::SwapNotFound { swap_hash: swap_hash.clone() })?;
+
+    if matches!(swap.status, SwapStatus::Completed | SwapStatus::Diverged | SwapStatus::Refunded) {
+        return Err(ContractError::InvalidSwapStatus { swap_hash, status: swap.status });
+    }
+
+    let now = env.block.time.seconds();
+    if swap.status != SwapStatus::Refunding && !swap.src.is_expired(now) && !swap.dst.is_expired(now) {
+        return Err(ContractError::SwapNotExpired { swap_hash });
+    }
+    swap.status = SwapStatus::Refunding;
+
+    let mut msgs = vec![];
+    for leg in [&mut swap.src, &mut swap.dst] {
+        if leg.status == LegStatus::Funded && now >= leg.timelocks.public_cancellation {
+            let packet = HubPacket::CancelEscrow { swap_hash: swap_hash.clone() };
+            msgs.push(send_to_spoke(deps.as_ref(), &env, &leg.chain, &packet)?);
+            leg.status = LegStatus::Cancelling;
         }
     }
+
+    swap.settle();
+    SWAPS.save(deps.storage, swap_hash.clone(), &swap)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "refund_swap")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("status", format!("{:?}", swap.status)))
+}
+
+/// Pass the revealed secret to every funded escrow of the swap in its public withdrawal stage.
+/// The hub relays a secret as soon as it learns it only when that stage has already started.
+fn relay_secret(deps: DepsMut, env: Env, swap_hash: String) -> Result<Response, ContractError> {
+    let mut swap = SWAPS
+        .may_load(deps.storage, swap_hash.clone())?
+        .ok_or_else(|| ContractError::SwapNotFound { swap_hash: swap_hash.clone() })?;
+
+    let Some(secret) = swap.secret.clone() else {
+        return Err(ContractError::NothingToReveal { swap_hash });
+    };
+
+    let mut msgs = vec![];
+    for leg in [&mut swap.src, &mut swap.dst] {
+        msgs.extend(reveal_to_leg(deps.as_ref(), &env, &swap_hash, &secret, leg)?);
+    }
+    if msgs.is_empty() {
+        return Err(ContractError::NothingToReveal { swap_hash });
+    }
+
+    SWAPS.save(deps.storage, swap_hash.clone(), &swap)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "relay_secret")
+        .add_attribute("swap_hash", swap_hash))
+}
+
+// ================================================================================================
+// ADMIN
+// ================================================================================================
+
+fn set_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    chain: String,
+    channel_id: String,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    if let Some(previous) = ROUTES.may_load(deps.storage, chain.clone())? {
+        CHANNEL_CHAINS.remove(deps.storage, previous);
+    }
+    ROUTES.save(deps.storage, chain.clone(), &channel_id)?;
+    CHANNEL_CHAINS.save(deps.storage, channel_id.clone(), &chain)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_route")
+        .add_attribute("chain", chain)
+        .add_attribute("channel", channel_id))
 }
 
-#[entry_point]
+fn update_resolvers(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    for resolver in &add {
+        let resolver = deps.api.addr_validate(resolver)?;
+        RESOLVERS.save(deps.storage, &resolver, &true)?;
+    }
+    for resolver in &remove {
+        let resolver = deps.api.addr_validate(resolver)?;
+        RESOLVERS.remove(deps.storage, &resolver);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "update_resolvers")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+fn update_packet_timeout(
+    deps: DepsMut,
+    info: MessageInfo,
+    packet_timeout_seconds: u64,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    // Validate packet timeout
+    if packet_timeout_seconds == 0 {
+        return Err(ContractError::InvalidPacketTimeout);
+    }
+    CONFIG.update(deps.storage, |config| -> StdResult<_> {
+        Ok(Config { packet_timeout_seconds, ..config })
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_packet_timeout")
+        .add_attribute("packet_timeout_seconds", packet_timeout_seconds.to_string()))
+}
+
+fn update_admin(deps: DepsMut, info: MessageInfo, admin: String) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    // Validate new admin address
+    let new_admin = deps.api.addr_validate(&admin)?;
+    CONFIG.update(deps.storage, |config| -> StdResult<_> {
+        Ok(Config { admin: new_admin.clone(), ..config })
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_admin")
+        .add_attribute("new_admin", new_admin))
+}
+
+fn ensure_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    if *sender != CONFIG.load(deps.storage)?.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can perform this action".to_string(),
+        });
+    }
+    Ok(())
+}
+
+// ================================================================================================
+// QUERIES
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
+        QueryMsg::GetSwap { swap_hash } => to_json_binary(&query_swap(deps, swap_hash)?),
+        QueryMsg::ListSwaps { status, start_after, limit } => {
+            to_json_binary(&query_list_swaps(deps, status, start_after, limit)?)
+        }
+        QueryMsg::ListRoutes {} => to_json_binary(&query_list_routes(deps)?),
+        QueryMsg::ListResolvers {} => to_json_binary(&query_list_resolvers(deps)?),
         QueryMsg::GetConfig {} => {
             let cfg = CONFIG.load(deps.storage)?;
-            to_json_binary(&ConfigResponse { admin: cfg.admin, htlc_factory: cfg.htlc_factory, ibc_channel: cfg.ibc_channel })
+            to_json_binary(&ConfigResponse {
+                admin: cfg.admin,
+                packet_timeout_seconds: cfg.packet_timeout_seconds,
+            })
         }
     }
 }
 
+fn query_swap(deps: Deps, swap_hash: String) -> StdResult<SwapInfo> {
+    SWAPS.load(deps.storage, swap_hash).map(SwapInfo::from)
+}
+
+fn query_list_swaps(
+    deps: Deps,
+    status: Option<SwapStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<SwapInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // A status narrows the range to its index prefix, so rare statuses don't scan every swap
+    let swaps = match status {
+        Some(status) => SWAPS.idx.status.prefix(status_key(&status)).range(
+            deps.storage,
+            start,
+            None,
+            cosmwasm_std::Order::Ascending,
+        ),
+        None => SWAPS.range(deps.storage, start, None, cosmwasm_std::Order::Ascending),
+    };
+    swaps
+        .take(limit)
+        .map(|item| item.map(|(_, swap)| swap.into()))
+        .collect()
+}
+
+fn query_list_routes(deps: Deps) -> StdResult<Vec<RouteInfo>> {
+    ROUTES
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(chain, channel_id)| RouteInfo { chain, channel_id }))
+        .collect()
+}
 
+fn query_list_resolvers(deps: Deps) -> StdResult<Vec<Addr>> {
+    RESOLVERS
+        .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .collect()
+}
@@ -1,6 +1,8 @@
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
+use crate::msg::SwapStatus;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -12,11 +14,42 @@ pub enum ContractError {
     #[error("Invalid IBC channel: {channel}")]
     InvalidIBCChannel { channel: String },
 
+    #[error("Only unordered channels are supported")]
+    InvalidIBCOrdering,
+
+    #[error("Invalid IBC version: {version}")]
+    InvalidIBCVersion { version: String },
+
     #[error("Invalid packet data")]
     InvalidPacketData,
 
     #[error("Invalid message format")]
     InvalidMessageFormat,
-}
 
+    #[error("No route to chain {chain}")]
+    RouteNotFound { chain: String },
+
+    #[error("Swap not found: {swap_hash}")]
+    SwapNotFound { swap_hash: String },
+
+    #[error("Swap already exists: {swap_hash}")]
+    SwapAlreadyExists { swap_hash: String },
 
+    #[error("Packet timeout must be greater than zero")]
+    InvalidPacketTimeout,
+
+    #[error("Invalid swap: {msg}")]
+    InvalidSwap { msg: String },
+
+    #[error("Swap {swap_hash} is {status:?}")]
+    InvalidSwapStatus { swap_hash: String, status: SwapStatus },
+
+    #[error("No leg of swap {swap_hash} has expired")]
+    SwapNotExpired { swap_hash: String },
+
+    #[error("Secret doesn't match the hashlock of swap {swap_hash}")]
+    InvalidSecret { swap_hash: String },
+
+    #[error("No secret of swap {swap_hash} can be relayed yet")]
+    NothingToReveal { swap_hash: String },
+}
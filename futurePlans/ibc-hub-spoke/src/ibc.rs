@@ -1,85 +1,474 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, DepsMut, Env, Response, StdResult, WasmMsg, to_json_binary,
-    IbcChannelOpenMsg, IbcChannelConnectMsg, IbcChannelCloseMsg,
-    IbcPacketReceiveMsg, IbcPacketAckMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
-    Binary,
+    from_json, to_json_binary, Binary, Deps, DepsMut, Env, Ibc3ChannelOpenResponse,
+    IbcBasicResponse, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcChannelOpenResponse, IbcMsg, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg,
+    IbcReceiveResponse, IbcTimeout, Never, Response, Uint128,
 };
-use crate::state::CONFIG;
-
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
-pub enum IbcAction {
-    CreateHTLC {
-        swap_hash: String,
-        maker: String,
-        amount: String,
-        denom: String,
-        hashlock: Vec<u8>,
-        timelock: u64,
-    },
-    UpdateStatus {
-        swap_hash: String,
-        status: String,
-    },
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
-pub struct IbcPacket {
-    pub action: IbcAction,
-}
-
-#[entry_point]
-pub fn ibc_channel_open(_deps: DepsMut, _env: Env, _msg: IbcChannelOpenMsg) -> StdResult<()> {
-    Ok(())
+use htlc_msgs::types::{EscrowMode, HashAlgorithm, SwapStatus as EscrowStatus, Timelocks};
+
+use crate::error::ContractError;
+use crate::msg::{Leg, LegStatus, SwapStatus};
+use crate::packet::{
+    ack_fail, ack_success, Ack, EscrowCreated, HubPacket, SpokePacket, IBC_ORDERING, IBC_VERSION,
+};
+use crate::state::{Swap, CHANNELS, CHANNEL_CHAINS, CONFIG, ROUTES, SWAPS};
+
+// ================================================================================================
+// CHANNEL LIFECYCLE
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    validate_order_and_version(msg.channel(), msg.counterparty_version())?;
+
+    Ok(Some(Ibc3ChannelOpenResponse {
+        version: IBC_VERSION.to_string(),
+    }))
 }
 
-#[entry_point]
-pub fn ibc_channel_connect(_deps: DepsMut, _env: Env, _msg: IbcChannelConnectMsg) -> StdResult<()> {
-    Ok(())
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    validate_order_and_version(channel, msg.counterparty_version())?;
+
+    let channel_id = channel.endpoint.channel_id.clone();
+    CHANNELS.save(deps.storage, channel_id.clone(), &true)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ibc_channel_connect")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("connection_id", channel.connection_id.clone()))
 }
 
-#[entry_point]
-pub fn ibc_channel_close(_deps: DepsMut, _env: Env, _msg: IbcChannelCloseMsg) -> StdResult<()> {
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+    CHANNELS.remove(deps.storage, channel_id.clone());
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ibc_channel_close")
+        .add_attribute("channel_id", channel_id))
+}
+
+fn validate_order_and_version(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.order != IBC_ORDERING {
+        return Err(ContractError::InvalidIBCOrdering);
+    }
+
+    if channel.version != IBC_VERSION {
+        return Err(ContractError::InvalidIBCVersion {
+            version: channel.version.clone(),
+        });
+    }
+
+    // Only set during OpenTry/OpenAck
+    if let Some(version) = counterparty_version {
+        if version != IBC_VERSION {
+            return Err(ContractError::InvalidIBCVersion {
+                version: version.to_string(),
+            });
+        }
+    }
+
     Ok(())
 }
 
-#[entry_point]
-pub fn ibc_packet_receive(deps: DepsMut, _env: Env, msg: IbcPacketReceiveMsg) -> StdResult<IbcReceiveResponse> {
-    let data: Binary = msg.packet.data;
-    let packet: IbcPacket = serde_json::from_slice(&data).map_err(|e| cosmwasm_std::StdError::generic_err(e.to_string()))?;
-
-    match packet.action {
-        IbcAction::CreateHTLC { swap_hash, maker, amount, denom, hashlock, timelock } => {
-            let cfg = CONFIG.load(deps.storage)?;
-            let exec_msg = to_json_binary(&serde_json::json!({
-                "create_h_t_l_c": {
-                    "swap_hash": swap_hash,
-                    "maker": maker,
-                    "amount": amount,
-                    "denom": denom,
-                    "hashlock": hashlock,
-                    "timelock": timelock
-                }
-            }))?;
-            let exec = WasmMsg::Execute { contract_addr: cfg.htlc_factory.to_string(), msg: exec_msg, funds: vec![] };
-            Ok(IbcReceiveResponse::new()
-                .set_ack(Binary::from(b"ok".to_vec()))
-                .add_message(exec))
+// ================================================================================================
+// PACKET LIFECYCLE
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, Never> {
+    // Failures become error acknowledgements so the spoke learns about them
+    let res = receive_packet(deps, env, &msg.packet.dest.channel_id, &msg.packet.data).and_then(|res| {
+        Ok(IbcReceiveResponse::new()
+            .set_ack(ack_success(Binary::default())?)
+            .add_submessages(res.messages)
+            .add_attributes(res.attributes))
+    });
+
+    Ok(res.unwrap_or_else(|err| {
+        let error = err.to_string();
+        IbcReceiveResponse::new()
+            .set_ack(ack_fail(error.clone()).unwrap_or_default())
+            .add_attribute("method", "ibc_packet_receive")
+            .add_attribute("error", error)
+    }))
+}
+
+fn receive_packet(deps: DepsMut, env: Env, channel_id: &str, data: &Binary) -> Result<Response, ContractError> {
+    // Only spokes with a route can report
+    let chain = CHANNEL_CHAINS
+        .may_load(deps.storage, channel_id.to_string())?
+        .ok_or_else(|| ContractError::InvalidIBCChannel { channel: channel_id.to_string() })?;
+
+    let packet: SpokePacket = from_json(data).map_err(|_| ContractError::InvalidPacketData)?;
+    match packet {
+        SpokePacket::SwapRequested(request) => {
+            let request = *request;
+            let src = new_leg(chain, request.maker, request.amount, request.denom, request.timelocks);
+            let dst = new_leg(
+                request.dst_chain,
+                request.receiver,
+                request.dst_amount,
+                request.dst_denom,
+                request.dst_timelocks,
+            );
+            swap_requested(deps, env, request.swap_hash, request.hashlock, src, dst)
+        }
+        SpokePacket::EscrowStatus { swap_hash, status } => {
+            escrow_status(deps, env, &chain, swap_hash, status)
+        }
+        SpokePacket::SecretRevealed { swap_hash, secret } => {
+            secret_revealed(deps, env, &chain, swap_hash, secret)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let packet: HubPacket = from_json(&msg.original_packet.data)?;
+    let result = match from_json::<Ack>(&msg.acknowledgement.data)? {
+        Ack::Result(data) => Ok(data),
+        Ack::Error(error) => Err(error),
+    };
+
+    on_packet_result(deps, &msg.original_packet.src.channel_id, packet, result)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let packet: HubPacket = from_json(&msg.packet.data)?;
+    on_packet_result(deps, &msg.packet.src.channel_id, packet, Err("packet timed out".to_string()))
+}
+
+// ================================================================================================
+// SPOKE REPORTS
+// ================================================================================================
+
+fn new_leg(chain: String, maker: String, amount: Uint128, denom: String, timelocks: Timelocks) -> Leg {
+    Leg {
+        chain,
+        maker,
+        resolver: None,
+        amount,
+        denom,
+        timelocks,
+        escrow: None,
+        status: LegStatus::Pending,
+    }
+}
+
+/// Record a swap requested by a maker on the source spoke; it waits for a resolver.
+fn swap_requested(
+    deps: DepsMut,
+    env: Env,
+    swap_hash: String,
+    hashlock: Vec<u8>,
+    src: Leg,
+    dst: Leg,
+) -> Result<Response, ContractError> {
+    let invalid = |msg: &str| ContractError::InvalidSwap { msg: msg.to_string() };
+
+    // Validate swap
+    if SWAPS.has(deps.storage, swap_hash.clone()) {
+        return Err(ContractError::SwapAlreadyExists { swap_hash });
+    }
+    if hashlock.len() != HashAlgorithm::Sha256.digest_len() {
+        return Err(invalid("hashlock must be a SHA-256 digest"));
+    }
+    if src.amount.is_zero() || dst.amount.is_zero() {
+        return Err(invalid("amounts must be greater than zero"));
+    }
+    if src.chain == dst.chain {
+        return Err(invalid("source and destination chains must differ"));
+    }
+    if !ROUTES.has(deps.storage, dst.chain.clone()) {
+        return Err(ContractError::RouteNotFound { chain: dst.chain });
+    }
+
+    // Validate timelocks; the resolver's escrow must expire first so the maker can't be
+    // refunded on the source chain while still able to withdraw on the destination chain
+    let now = env.block.time.seconds();
+    if !src.timelocks.is_valid(now) || !dst.timelocks.is_valid(now) {
+        return Err(invalid("timelocks must be ordered and not expired"));
+    }
+    if dst.timelocks.cancellation >= src.timelocks.cancellation {
+        return Err(invalid("destination escrow must be cancellable before the source escrow"));
+    }
+
+    let swap = Swap {
+        swap_hash: swap_hash.clone(),
+        hashlock,
+        src,
+        dst,
+        resolver: None,
+        status: SwapStatus::Open,
+        secret: None,
+        failure_reason: None,
+        created_at: env.block.time,
+    };
+    SWAPS.save(deps.storage, swap_hash.clone(), &swap)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "swap_requested")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("src_chain", swap.src.chain)
+        .add_attribute("dst_chain", swap.dst.chain))
+}
+
+/// Track a leg's escrow. Once the maker has funded the source escrow, the destination spoke
+/// is asked to create the resolver's escrow.
+fn escrow_status(
+    deps: DepsMut,
+    env: Env,
+    chain: &str,
+    swap_hash: String,
+    status: EscrowStatus,
+) -> Result<Response, ContractError> {
+    let mut swap = load_swap(deps.as_ref(), &swap_hash)?;
+    swap.leg_mut(chain)
+        .ok_or_else(|| ContractError::InvalidIBCChannel { channel: chain.to_string() })?
+        .apply_report(&status);
+
+    let mut res = Response::new();
+    if swap.status == SwapStatus::Matched
+        && swap.src.status == LegStatus::Funded
+        && swap.dst.status == LegStatus::Pending
+    {
+        let packet = HubPacket::CreateEscrow {
+            swap_hash: swap_hash.clone(),
+            mode: EscrowMode::Destination,
+            maker: swap.dst.maker.clone(),
+            resolver: swap.dst.resolver.clone(),
+            amount: swap.dst.amount,
+            denom: swap.dst.denom.clone(),
+            hashlock: swap.hashlock.clone(),
+            timelocks: swap.dst.timelocks.clone(),
+        };
+        res = res.add_message(send_to_spoke(deps.as_ref(), &env, &swap.dst.chain, &packet)?);
+        swap.dst.status = LegStatus::Requested;
+    }
+
+    swap.settle();
+    SWAPS.save(deps.storage, swap_hash.clone(), &swap)?;
+
+    Ok(res
+        .add_attribute("method", "escrow_status")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("chain", chain)
+        .add_attribute("status", format!("{:?}", status)))
+}
+
+/// Record a secret revealed on one leg and pass it to the other leg's escrow, so the maker
+/// withdrawing on the destination chain also pays out the resolver on the source chain. Until
+/// that escrow's public withdrawal stage only its resolver can withdraw, using the secret now
+/// public on the hub; `RelaySecret` passes it on once the stage starts.
+fn secret_revealed(
+    deps: DepsMut,
+    env: Env,
+    chain: &str,
+    swap_hash: String,
+    secret: Vec<u8>,
+) -> Result<Response, ContractError> {
+    let mut swap = load_swap(deps.as_ref(), &swap_hash)?;
+
+    // Validate secret
+    if HashAlgorithm::Sha256.digest(&secret) != swap.hashlock {
+        return Err(ContractError::InvalidSecret { swap_hash });
+    }
+    if swap.secret.is_some() {
+        return Ok(Response::new()
+            .add_attribute("method", "secret_revealed")
+            .add_attribute("swap_hash", swap_hash)
+            .add_attribute("skipped", "already_revealed"));
+    }
+
+    let counterpart = if swap.src.chain == chain {
+        &mut swap.dst
+    } else if swap.dst.chain == chain {
+        &mut swap.src
+    } else {
+        return Err(ContractError::InvalidIBCChannel { channel: chain.to_string() });
+    };
+
+    let mut res = Response::new();
+    if let Some(msg) = reveal_to_leg(deps.as_ref(), &env, &swap_hash, &secret, counterpart)? {
+        res = res.add_message(msg);
+    }
+
+    swap.secret = Some(secret);
+    SWAPS.save(deps.storage, swap_hash.clone(), &swap)?;
+
+    Ok(res
+        .add_attribute("method", "secret_revealed")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("chain", chain))
+}
+
+// ================================================================================================
+// HUB PACKET RESULTS
+// ================================================================================================
+
+/// Apply the spoke's answer to a packet we sent, or `Err` when it failed or timed out.
+/// A failed escrow creation sends the swap into refunding. A failed reveal leaves the leg
+/// funded: the secret is public on the hub, so the resolver can still withdraw directly and
+/// `RelaySecret` can try again.
+fn on_packet_result(
+    deps: DepsMut,
+    channel_id: &str,
+    packet: HubPacket,
+    result: Result<Binary, String>,
+) -> Result<IbcBasicResponse, ContractError> {
+    let chain = CHANNEL_CHAINS
+        .may_load(deps.storage, channel_id.to_string())?
+        .ok_or_else(|| ContractError::InvalidIBCChannel { channel: channel_id.to_string() })?;
+
+    let (swap_hash, action) = match &packet {
+        HubPacket::CreateEscrow { swap_hash, .. } => (swap_hash.clone(), "create_escrow"),
+        HubPacket::RevealSecret { swap_hash, .. } => (swap_hash.clone(), "reveal_secret"),
+        HubPacket::CancelEscrow { swap_hash } => (swap_hash.clone(), "cancel_escrow"),
+    };
+
+    let mut swap = load_swap(deps.as_ref(), &swap_hash)?;
+    let leg = swap
+        .leg_mut(&chain)
+        .ok_or_else(|| ContractError::InvalidIBCChannel { channel: channel_id.to_string() })?;
+
+    let error = match (packet, result) {
+        (HubPacket::CreateEscrow { .. }, Ok(data)) => {
+            let created: EscrowCreated = from_json(&data)?;
+            leg.escrow = Some(created.escrow_address);
+            if leg.status == LegStatus::Requested {
+                leg.status = LegStatus::Created;
+            }
+            None
+        }
+        (HubPacket::CreateEscrow { .. }, Err(error)) => {
+            if leg.status == LegStatus::Requested {
+                leg.status = LegStatus::Failed;
+            }
+            Some(error)
+        }
+        (HubPacket::CancelEscrow { .. }, Ok(_)) => {
+            if leg.status == LegStatus::Cancelling {
+                leg.status = LegStatus::Cancelled;
+            }
+            None
         }
-        IbcAction::UpdateStatus { .. } => {
-            // No-op for now; acknowledge
-            Ok(IbcReceiveResponse::new().set_ack(Binary::from(b"ok".to_vec())))
+        (HubPacket::CancelEscrow { .. }, Err(error)) => {
+            // Still funded; refunding can be retried
+            if leg.status == LegStatus::Cancelling {
+                leg.status = LegStatus::Funded;
+            }
+            Some(error)
         }
+        // Completion arrives with the spoke's status report
+        (HubPacket::RevealSecret { .. }, Ok(_)) => None,
+        (HubPacket::RevealSecret { .. }, Err(error)) => {
+            if leg.status == LegStatus::Revealing {
+                leg.status = LegStatus::Funded;
+            }
+            Some(error)
+        }
+    };
+
+    if let Some(error) = &error {
+        swap.failure_reason = Some(format!("{} on {} failed: {}", action, chain, error));
     }
+    swap.settle();
+    SWAPS.save(deps.storage, swap_hash.clone(), &swap)?;
+
+    let mut res = IbcBasicResponse::new()
+        .add_attribute("method", "ibc_packet_result")
+        .add_attribute("action", action)
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("success", error.is_none().to_string());
+    if let Some(error) = error {
+        res = res.add_attribute("error", error);
+    }
+    Ok(res)
 }
 
-#[entry_point]
-pub fn ibc_packet_ack(_deps: DepsMut, _env: Env, _msg: IbcPacketAckMsg) -> StdResult<Response> {
-    Ok(Response::new())
+// ================================================================================================
+// HELPERS
+// ================================================================================================
+
+/// `RevealSecret` packet for the leg's spoke, if the leg can take the secret now.
+pub(crate) fn reveal_to_leg(
+    deps: Deps,
+    env: &Env,
+    swap_hash: &str,
+    secret: &[u8],
+    leg: &mut Leg,
+) -> Result<Option<IbcMsg>, ContractError> {
+    if !leg.can_reveal(env.block.time.seconds()) {
+        return Ok(None);
+    }
+
+    let packet = HubPacket::RevealSecret {
+        swap_hash: swap_hash.to_string(),
+        secret: secret.to_vec(),
+    };
+    let msg = send_to_spoke(deps, env, &leg.chain, &packet)?;
+    leg.status = LegStatus::Revealing;
+    Ok(Some(msg))
 }
 
-#[entry_point]
-pub fn ibc_packet_timeout(_deps: DepsMut, _env: Env, _msg: IbcPacketTimeoutMsg) -> StdResult<Response> {
-    Ok(Response::new())
+fn load_swap(deps: Deps, swap_hash: &str) -> Result<Swap, ContractError> {
+    SWAPS
+        .may_load(deps.storage, swap_hash.to_string())?
+        .ok_or_else(|| ContractError::SwapNotFound { swap_hash: swap_hash.to_string() })
 }
 
+/// Packet for the spoke on `chain`, over the open channel of its route.
+pub(crate) fn send_to_spoke(
+    deps: Deps,
+    env: &Env,
+    chain: &str,
+    packet: &HubPacket,
+) -> Result<IbcMsg, ContractError> {
+    let channel_id = ROUTES
+        .may_load(deps.storage, chain.to_string())?
+        .ok_or_else(|| ContractError::RouteNotFound { chain: chain.to_string() })?;
+    if !CHANNELS.has(deps.storage, channel_id.clone()) {
+        return Err(ContractError::InvalidIBCChannel { channel: channel_id });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    Ok(IbcMsg::SendPacket {
+        channel_id,
+        data: to_json_binary(packet)?,
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(config.packet_timeout_seconds)),
+    })
+}
 
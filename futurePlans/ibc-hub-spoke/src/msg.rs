@@ -1,51 +1,154 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+
+pub use htlc_msgs::types::Timelocks;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub admin: String,
-    pub htlc_factory: String,
-    pub ibc_channel: String,
+    // Lifetime of packets sent to spokes
+    pub packet_timeout_seconds: u64,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    CreateOrder {
+    // Resolver takes an open swap; `src_resolver` is its account on the source chain, paid
+    // from the source escrow once the secret is revealed, and `dst_resolver` its account on the
+    // destination chain, the only one allowed to fund the destination escrow
+    AcceptSwap {
         swap_hash: String,
-        maker: String,
-        amount: String,
-        denom: String,
-        hashlock: Vec<u8>,
-        timelock: u64,
-        target_chain: String,
+        src_resolver: String,
+        dst_resolver: String,
     },
+
+    // Cancel the escrows of a swap whose legs reached their cancellation stage; anyone may
+    // call it
+    RefundSwap {
+        swap_hash: String,
+    },
+
+    // Pass a revealed secret to the funded escrows of a swap once their public withdrawal stage
+    // has started; anyone may call it
+    RelaySecret {
+        swap_hash: String,
+    },
+
+    // Admin functions
     SetRoute {
         chain: String,
         channel_id: String,
     },
-    SendCreateHTLC {
-        swap_hash: String,
-        maker: String,
-        amount: String,
-        denom: String,
-        hashlock: Vec<u8>,
-        timelock: u64,
-        dest_chain: String,
+
+    UpdateResolvers {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+
+    UpdatePacketTimeout {
+        packet_timeout_seconds: u64,
+    },
+
+    UpdateAdmin {
+        admin: String,
     },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    #[returns(SwapInfo)]
+    GetSwap { swap_hash: String },
+
+    #[returns(Vec<SwapInfo>)]
+    ListSwaps {
+        status: Option<SwapStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Vec<RouteInfo>)]
+    ListRoutes {},
+
+    #[returns(Vec<Addr>)]
+    ListResolvers {},
+
     #[returns(ConfigResponse)]
     GetConfig {},
 }
 
+/// Progress of a swap across both legs.
 #[cw_serde]
-pub struct ConfigResponse {
-    pub admin: Addr,
-    pub htlc_factory: Addr,
-    pub ibc_channel: String,
+pub enum SwapStatus {
+    /// Requested by the maker, waiting for a resolver
+    Open,
+    /// Taken by a resolver; escrows are being created and funded
+    Matched,
+    /// Both escrows paid out
+    Completed,
+    /// One escrow paid out with the secret while the other was refunded
+    Diverged,
+    /// A leg timed out or failed; funded escrows are cancelled as they expire
+    Refunding,
+    /// No escrow of the swap holds funds anymore
+    Refunded,
+}
+
+/// Progress of the escrow on one chain.
+#[cw_serde]
+pub enum LegStatus {
+    /// Escrow not requested yet
+    Pending,
+    /// `CreateEscrow` sent to the spoke
+    Requested,
+    Created,
+    Funded,
+    /// `CancelEscrow` sent to the spoke
+    Cancelling,
+    /// `RevealSecret` sent to the spoke
+    Revealing,
+    Completed,
+    Cancelled,
+    /// The spoke couldn't create the escrow
+    Failed,
+}
+
+/// One side of a swap: the escrow on `chain` paying `maker` or refunding it.
+#[cw_serde]
+pub struct Leg {
+    pub chain: String,
+    /// Maker's account on `chain`
+    pub maker: String,
+    pub resolver: Option<String>,
+    pub amount: Uint128,
+    pub denom: String,
+    pub timelocks: Timelocks,
+    pub escrow: Option<String>,
+    pub status: LegStatus,
 }
 
+#[cw_serde]
+pub struct SwapInfo {
+    pub swap_hash: String,
+    pub hashlock: Vec<u8>,
+    /// Leg locking the maker's funds
+    pub src: Leg,
+    /// Leg locking the resolver's funds
+    pub dst: Leg,
+    pub resolver: Option<Addr>,
+    pub status: SwapStatus,
+    pub secret: Option<Vec<u8>>,
+    pub failure_reason: Option<String>,
+    pub created_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct RouteInfo {
+    pub chain: String,
+    pub channel_id: String,
+}
 
+#[cw_serde]
+pub struct ConfigResponse {
+    pub admin: Addr,
+    pub packet_timeout_seconds: u64,
+}
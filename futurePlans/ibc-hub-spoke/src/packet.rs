@@ -0,0 +1,94 @@
+//! Packets exchanged between the hub and its spokes.
+//!
+//! The hub tells spokes which escrows to create, reveal or cancel; spokes report what their
+//! escrows did. Every packet is answered with an `Ack`.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_binary, Binary, IbcOrder, StdResult, Uint128};
+use htlc_msgs::types::{EscrowMode, SwapStatus, Timelocks};
+
+/// Channel version both ends must agree on.
+pub const IBC_VERSION: &str = "htlc-hub-spoke-1";
+
+/// Swaps are keyed by swap hash, so packets don't need to arrive in sequence.
+pub const IBC_ORDERING: IbcOrder = IbcOrder::Unordered;
+
+/// Packets the hub sends to a spoke.
+#[cw_serde]
+pub enum HubPacket {
+    /// Create the local escrow of one leg; the ack carries `EscrowCreated`
+    CreateEscrow {
+        swap_hash: String,
+        mode: EscrowMode,
+        maker: String,
+        resolver: Option<String>,
+        amount: Uint128,
+        denom: String,
+        hashlock: Vec<u8>,
+        timelocks: Timelocks,
+    },
+    /// Complete the local escrow with a secret revealed on the other leg
+    RevealSecret {
+        swap_hash: String,
+        secret: Vec<u8>,
+    },
+    /// Refund the local escrow once its cancellation stage has started
+    CancelEscrow {
+        swap_hash: String,
+    },
+}
+
+/// Packets a spoke sends to the hub.
+#[cw_serde]
+pub enum SpokePacket {
+    /// A maker on the sending spoke asks for a swap
+    SwapRequested(Box<SwapRequest>),
+    /// The spoke's escrow for the swap changed status
+    EscrowStatus {
+        swap_hash: String,
+        status: SwapStatus,
+    },
+    /// The spoke's escrow for the swap was opened with `secret`
+    SecretRevealed {
+        swap_hash: String,
+        secret: Vec<u8>,
+    },
+}
+
+/// Swap a maker on the sending spoke asks for; that spoke is the source chain.
+#[cw_serde]
+pub struct SwapRequest {
+    pub swap_hash: String,
+    pub maker: String,
+    pub amount: Uint128,
+    pub denom: String,
+    pub hashlock: Vec<u8>,
+    pub timelocks: Timelocks,
+    pub dst_chain: String,
+    /// Maker's account on `dst_chain`
+    pub receiver: String,
+    pub dst_amount: Uint128,
+    pub dst_denom: String,
+    pub dst_timelocks: Timelocks,
+}
+
+/// Acknowledgement written for every received packet.
+#[cw_serde]
+pub enum Ack {
+    Result(Binary),
+    Error(String),
+}
+
+/// Result of a `CreateEscrow` packet.
+#[cw_serde]
+pub struct EscrowCreated {
+    pub escrow_address: String,
+}
+
+pub fn ack_success(data: Binary) -> StdResult<Binary> {
+    to_json_binary(&Ack::Result(data))
+}
+
+pub fn ack_fail(error: impl Into<String>) -> StdResult<Binary> {
+    to_json_binary(&Ack::Error(error.into()))
+}
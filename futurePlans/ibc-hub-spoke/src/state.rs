@@ -1,13 +1,118 @@
-use cosmwasm_std::Addr;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Timestamp};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use htlc_msgs::types::{SwapStatus as EscrowStatus, TimelockStage};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{Leg, LegStatus, SwapInfo, SwapStatus};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub admin: Addr,
-    pub htlc_factory: Addr,
-    pub ibc_channel: String,
+    /// Packets to spokes not relayed within this time out and count as failed
+    pub packet_timeout_seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Swap {
+    pub swap_hash: String,
+    pub hashlock: Vec<u8>,
+    pub src: Leg,
+    pub dst: Leg,
+    pub resolver: Option<Addr>,
+    pub status: SwapStatus,
+    pub secret: Option<Vec<u8>>,
+    pub failure_reason: Option<String>,
+    pub created_at: Timestamp,
+}
+
+impl Swap {
+    /// Leg whose escrow lives on `chain`.
+    pub fn leg_mut(&mut self, chain: &str) -> Option<&mut Leg> {
+        if self.src.chain == chain {
+            Some(&mut self.src)
+        } else if self.dst.chain == chain {
+            Some(&mut self.dst)
+        } else {
+            None
+        }
+    }
+
+    /// Move to `Refunding` when a leg failed and to a final status once both legs settled.
+    pub fn settle(&mut self) {
+        let legs = [&self.src, &self.dst];
+
+        if legs.iter().all(|leg| leg.status == LegStatus::Completed) {
+            self.status = SwapStatus::Completed;
+            return;
+        }
+
+        // The secret was revealed, yet one escrow was cancelled before it paid out
+        if legs.iter().any(|leg| leg.status == LegStatus::Completed)
+            && legs.iter().any(|leg| leg.status == LegStatus::Cancelled)
+        {
+            self.status = SwapStatus::Diverged;
+            return;
+        }
+
+        if legs.iter().any(|leg| leg.status == LegStatus::Failed) && self.status == SwapStatus::Matched {
+            self.status = SwapStatus::Refunding;
+        }
+
+        // Escrows that were created but never funded hold nothing to refund
+        let holds_funds = legs.iter().any(|leg| {
+            matches!(
+                leg.status,
+                LegStatus::Requested | LegStatus::Funded | LegStatus::Cancelling | LegStatus::Revealing
+            )
+        });
+        if self.status == SwapStatus::Refunding && !holds_funds {
+            self.status = SwapStatus::Refunded;
+        }
+    }
+}
+
+impl Leg {
+    /// Whether the leg's escrow reached its cancellation stage at `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.timelocks.cancellation
+    }
+
+    /// Whether the leg's spoke may complete its escrow with the secret at `now`. The spoke
+    /// withdraws like any other account, so the escrow must be in its public withdrawal stage.
+    pub fn can_reveal(&self, now: u64) -> bool {
+        self.status == LegStatus::Funded && self.timelocks.stage(now) == TimelockStage::PublicWithdrawal
+    }
+
+    /// Apply a status reported by the leg's spoke. Reports can overtake the creation ack and
+    /// each other, so ones that would move the leg backwards are ignored.
+    pub fn apply_report(&mut self, status: &EscrowStatus) {
+        use LegStatus::*;
+
+        self.status = match (status, &self.status) {
+            (EscrowStatus::Pending, Requested) => Created,
+            (EscrowStatus::Funded, Requested | Created) => Funded,
+            (EscrowStatus::Completed, Requested | Created | Funded | Cancelling | Revealing) => Completed,
+            (EscrowStatus::Cancelled, Requested | Created | Funded | Cancelling | Revealing) => Cancelled,
+            (_, current) => current.clone(),
+        };
+    }
+}
+
+impl From<Swap> for SwapInfo {
+    fn from(swap: Swap) -> Self {
+        SwapInfo {
+            swap_hash: swap.swap_hash,
+            hashlock: swap.hashlock,
+            src: swap.src,
+            dst: swap.dst,
+            resolver: swap.resolver,
+            status: swap.status,
+            secret: swap.secret,
+            failure_reason: swap.failure_reason,
+            created_at: swap.created_at,
+        }
+    }
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -15,4 +120,42 @@ pub const CONFIG: Item<Config> = Item::new("config");
 // chain -> channel_id routing table for outbound packets
 pub const ROUTES: Map<String, String> = Map::new("routes");
 
+// channel_id -> chain of the spoke at the other end
+pub const CHANNEL_CHAINS: Map<String, String> = Map::new("channel_chains");
+
+// Open channels to spokes
+pub const CHANNELS: Map<String, bool> = Map::new("channels");
+
+/// Secondary indexes over swaps, keyed back to swap_hash.
+pub struct SwapIndexes<'a> {
+    pub status: MultiIndex<'a, String, Swap, String>,
+}
+
+impl<'a> IndexList<Swap> for SwapIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Swap>> + '_> {
+        let v: Vec<&dyn Index<Swap>> = vec![&self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn status_key(status: &SwapStatus) -> String {
+    match status {
+        SwapStatus::Open => "open",
+        SwapStatus::Matched => "matched",
+        SwapStatus::Completed => "completed",
+        SwapStatus::Diverged => "diverged",
+        SwapStatus::Refunding => "refunding",
+        SwapStatus::Refunded => "refunded",
+    }
+    .to_string()
+}
+
+pub const SWAPS: IndexedMap<String, Swap, SwapIndexes> = IndexedMap::new(
+    "swaps",
+    SwapIndexes {
+        status: MultiIndex::new(|_pk, s| status_key(&s.status), "swaps", "swaps__status"),
+    },
+);
 
+// Accounts allowed to take swaps
+pub const RESOLVERS: Map<&Addr, bool> = Map::new("resolvers");
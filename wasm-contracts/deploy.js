@@ -29,15 +29,15 @@ async function deployContracts() {
         }
     );
 
-    // Read contract wasm files (optimized by Docker)
+    // Read contract wasm files (optimized by Docker, one artifacts dir for the workspace)
     const escrowWasm = fs.readFileSync(
-        path.join(__dirname, 'escrow', 'artifacts', 'escrow.wasm')
+        path.join(__dirname, 'artifacts', 'escrow.wasm')
     );
     const escrowFactoryWasm = fs.readFileSync(
-        path.join(__dirname, 'escrowFactory', 'artifacts', 'escrow_factory.wasm')
+        path.join(__dirname, 'artifacts', 'escrow_factory.wasm')
     );
     const ibcWasm = fs.readFileSync(
-        path.join(__dirname, 'ibc', 'artifacts', 'ibc.wasm')
+        path.join(__dirname, 'artifacts', 'ibc.wasm')
     );
 
     // 1. Upload Escrow contract (individual contract)
@@ -81,7 +81,6 @@ async function deployContracts() {
     const ibcInitMsg = {
         admin: account.address,
         htlc_factory: escrowFactoryInstantiateResult.contractAddress,
-        ibc_channel: process.env.IBC_CHANNEL || 'channel-0',
     };
 
     const ibcInstantiateResult = await client.instantiate(
@@ -94,6 +93,35 @@ async function deployContracts() {
     );
     console.log('IBC contract instantiated:', ibcInstantiateResult);
 
+    // 6. Forward revealed secrets to the IBC contract so counterpart escrows complete
+    console.log('Setting Escrow Factory secret hook...');
+    await client.execute(
+        account.address,
+        escrowFactoryInstantiateResult.contractAddress,
+        { update_secret_hook: { hook: ibcInstantiateResult.contractAddress } },
+        'auto'
+    );
+
+    // 7. Register the counterparty chain route, if configured
+    if (process.env.IBC_TARGET_CHAIN && process.env.IBC_REMOTE_CONTRACT && process.env.IBC_REMOTE_FACTORY) {
+        console.log('Registering IBC route...');
+        await client.execute(
+            account.address,
+            ibcInstantiateResult.contractAddress,
+            {
+                set_route: {
+                    chain_id: process.env.IBC_TARGET_CHAIN,
+                    channel_id: process.env.IBC_CHANNEL || 'channel-0',
+                    remote_contract: process.env.IBC_REMOTE_CONTRACT,
+                    remote_factory: process.env.IBC_REMOTE_FACTORY,
+                    address_prefix: process.env.IBC_ADDRESS_PREFIX || 'osmo',
+                    timeout_seconds: Number(process.env.IBC_TIMEOUT_SECONDS || 300),
+                },
+            },
+            'auto'
+        );
+    }
+
     // Save deployment info
     const deploymentInfo = {
         network: 'osmosis',
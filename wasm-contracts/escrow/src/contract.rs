@@ -1,24 +1,31 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128, BankMsg, Addr,
+    from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128, BankMsg, Coin, CosmosMsg, WasmMsg, Reply, SubMsg, Event,
 };
-use sha2::{Sha256, Digest};
-use cw_utils::must_pay;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw_utils::NativeBalance;
+use htlc_msgs::factory::ExecuteMsg as FactoryExecuteMsg;
+use sha3::{Digest, Keccak256};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, SwapInfo, SwapStatus};
-use crate::state::{Escrow, ESCROW};
+use crate::msg::{
+    Asset, EscrowMode, ExecuteMsg, InstantiateMsg, MerkleProof, QueryMsg, ReceiveMsg,
+    RevealedSecret, SwapInfo, SwapStatus, TimelockStage, MERKLE_ROOT_LENGTH, SECRET_LENGTH,
+};
+use crate::state::{Escrow, ESCROW, FACTORY_CALLBACK_REPLY_ID};
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    // Validate timelock
-    if msg.timelock <= env.block.time.seconds() {
-        return Err(ContractError::TimelockExpired);
+    // Validate timelocks
+    if !msg.timelocks.is_valid(env.block.time.seconds()) {
+        return Err(ContractError::InvalidTimelocks);
     }
 
     // Validate amount
@@ -26,36 +33,120 @@ pub fn instantiate(
         return Err(ContractError::InvalidAmount);
     }
 
-    // Validate denom
-    if msg.denom.is_empty() {
-        return Err(ContractError::InvalidDenom);
+    // Validate asset
+    let asset = match msg.asset {
+        Asset::Native { denom } => {
+            if denom.is_empty() {
+                return Err(ContractError::InvalidDenom);
+            }
+            Asset::Native { denom }
+        }
+        Asset::Cw20 { contract_addr } => {
+            // CW20 funding arrives through the token contract, which cannot carry native coins
+            if msg.safety_deposit.is_some() {
+                return Err(ContractError::InvalidAsset {
+                    msg: "Safety deposit is not supported for CW20 escrows".to_string(),
+                });
+            }
+            Asset::Cw20 { contract_addr: deps.api.addr_validate(&contract_addr)?.to_string() }
+        }
+    };
+
+    // Validate hashlock length: a Merkle root for partial fills, otherwise a secret hash
+    let hashlock_len = match msg.parts {
+        Some(0) => return Err(ContractError::InvalidParts),
+        Some(_) => MERKLE_ROOT_LENGTH,
+        None => msg.hash_algorithm.digest_len(),
+    };
+    if msg.hashlock.len() != hashlock_len {
+        return Err(ContractError::InvalidHashlock {
+            expected: hashlock_len,
+            got: msg.hashlock.len(),
+        });
+    }
+
+    // Validate safety deposit
+    if let Some(deposit) = &msg.safety_deposit {
+        if deposit.amount.is_zero() {
+            return Err(ContractError::InvalidAmount);
+        }
+        if deposit.denom.is_empty() {
+            return Err(ContractError::InvalidDenom);
+        }
     }
 
     let maker_addr = deps.api.addr_validate(&msg.maker)?;
 
-    let swap = Escrow {
+    // The address is predictable, so refuse to start on funds nobody could account for
+    ensure_unfunded(deps.as_ref(), &env, &info, &asset)?;
+
+    // Destination escrows may be funded at instantiation (factory create-and-fund)
+    let funded = !info.funds.is_empty();
+
+    // Source escrows name their taker up front, destination escrows may leave it to the funder
+    let resolver = match (&msg.mode, funded) {
+        (EscrowMode::Source, false) | (EscrowMode::Destination, true) => {
+            let resolver = msg.resolver.ok_or_else(|| {
+                StdError::generic_err("Escrow requires a resolver")
+            })?;
+            Some(deps.api.addr_validate(&resolver)?)
+        }
+        (EscrowMode::Destination, false) => {
+            msg.resolver.as_deref().map(|r| deps.api.addr_validate(r)).transpose()?
+        }
+        (EscrowMode::Source, true) => {
+            return Err(ContractError::Unauthorized {
+                msg: "Only the maker can fund a source escrow".to_string(),
+            });
+        }
+    };
+
+    let mut swap = Escrow {
+        mode: msg.mode.clone(),
         maker: maker_addr,
-        resolver: None,
+        resolver,
         amount: msg.amount,
-        denom: msg.denom,
+        asset,
+        safety_deposit: msg.safety_deposit,
         hashlock: msg.hashlock,
-        timelock: msg.timelock,
+        hash_algorithm: msg.hash_algorithm,
+        parts: msg.parts,
+        filled_amount: Uint128::zero(),
+        used_secret_indices: vec![],
+        revealed_secrets: vec![],
+        timelocks: msg.timelocks.clone(),
         status: SwapStatus::Pending,
         created_at: env.block.time,
         funded_at: None,
         completed_at: None,
+        factory: msg.swap_hash.as_ref().map(|_| info.sender.clone()),
+        swap_hash: msg.swap_hash,
     };
 
+    if funded {
+        let Asset::Native { denom } = swap.asset.clone() else {
+            return Err(ContractError::InvalidAsset {
+                msg: "CW20 escrows are funded through the token contract".to_string(),
+            });
+        };
+        check_native_payment(&swap, &info, denom)?;
+        swap.status = SwapStatus::Funded;
+        swap.funded_at = Some(env.block.time);
+    }
+
     ESCROW.save(deps.storage, &swap)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
+        .add_attribute("status", format!("{:?}", swap.status))
+        .add_attribute("mode", format!("{:?}", msg.mode))
         .add_attribute("maker", msg.maker)
         .add_attribute("amount", msg.amount)
-        .add_attribute("timelock", msg.timelock.to_string()))
+        .add_attribute("withdrawal", msg.timelocks.withdrawal.to_string())
+        .add_attribute("cancellation", msg.timelocks.cancellation.to_string()))
 }
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
@@ -66,17 +157,31 @@ pub fn execute(
         ExecuteMsg::LockFunds { amount, denom } => {
             lock_funds(deps, env, info, amount, denom)
         }
-        ExecuteMsg::RevealSecret { secret } => {
-            reveal_secret(deps, env, info, secret)
+        ExecuteMsg::Receive(wrapper) => receive_cw20(deps, env, info, wrapper),
+        ExecuteMsg::RevealSecret { secret, merkle_proof } => {
+            reveal_secret(deps, env, info, secret, merkle_proof)
         }
         ExecuteMsg::CancelSwap {} => cancel_swap(deps, env, info),
     }
 }
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != FACTORY_CALLBACK_REPLY_ID {
+        return Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id: {}", msg.id))));
+    }
+
+    // A failing factory callback must never block withdrawals or refunds
+    let error = msg.result.into_result().err().unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("method", "factory_callback_failed")
+        .add_attribute("error", error))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::GetSwapInfo {} => to_json_binary(&query_swap_info(deps)?),
+        QueryMsg::GetSwapInfo {} => to_json_binary(&query_swap_info(deps, env)?),
     }
 }
 
@@ -91,12 +196,7 @@ fn lock_funds(
     amount: Uint128,
     denom: String,
 ) -> Result<Response, ContractError> {
-    let mut swap = ESCROW.load(deps.storage)?;
-
-    // Check if swap is in pending status
-    if swap.status != SwapStatus::Pending {
-        return Err(ContractError::SwapAlreadyCompleted);
-    }
+    let swap = ESCROW.load(deps.storage)?;
 
     // Validate amount matches
     if amount != swap.amount {
@@ -104,40 +204,144 @@ fn lock_funds(
     }
 
     // Validate denom matches
-    if denom != swap.denom {
+    if swap.asset != (Asset::Native { denom: denom.clone() }) {
         return Err(ContractError::InvalidDenom);
     }
 
-    // Check payment
-    let paid = must_pay(&info, &denom).map_err(|e| ContractError::InsufficientFunds { 
-        required: amount.to_string(), 
-        got: e.to_string() 
-    })?;
-    if paid != amount {
-        return Err(ContractError::InsufficientFunds { 
-            required: amount.to_string(), 
-            got: paid.to_string() 
+    check_native_payment(&swap, &info, denom)?;
+
+    fund_escrow(deps, env, swap, info.sender, amount)
+}
+
+/// Checks that the escrow holds nothing besides the coins sent with its instantiation, so funds
+/// sent to the predicted address ahead of time can't be mistaken for the swap's.
+fn ensure_unfunded(deps: Deps, env: &Env, info: &MessageInfo, asset: &Asset) -> Result<(), ContractError> {
+    let balances = deps.querier.query_all_balances(&env.contract.address)?;
+    let unaccounted = balances.iter().any(|balance| {
+        let sent = info
+            .funds
+            .iter()
+            .filter(|coin| coin.denom == balance.denom)
+            .map(|coin| coin.amount)
+            .sum::<Uint128>();
+        balance.amount > sent
+    });
+    if unaccounted {
+        return Err(ContractError::AddressAlreadyFunded);
+    }
+
+    if let Asset::Cw20 { contract_addr } = asset {
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            contract_addr,
+            &Cw20QueryMsg::Balance { address: env.contract.address.to_string() },
+        )?;
+        if !balance.balance.is_zero() {
+            return Err(ContractError::AddressAlreadyFunded);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that exactly the swap amount plus the safety deposit, if any, was sent.
+fn check_native_payment(swap: &Escrow, info: &MessageInfo, denom: String) -> Result<(), ContractError> {
+    let mut required = NativeBalance(vec![Coin { denom, amount: swap.amount }]);
+    if let Some(deposit) = &swap.safety_deposit {
+        required += deposit.clone();
+    }
+    required.normalize();
+    let mut paid = NativeBalance(info.funds.clone());
+    paid.normalize();
+    if paid != required {
+        return Err(ContractError::InsufficientFunds {
+            required: required.to_string(),
+            got: paid.to_string(),
         });
     }
+    Ok(())
+}
+
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::LockFunds {} => {
+            let swap = ESCROW.load(deps.storage)?;
+
+            // Only the escrowed token contract can deliver funds
+            match &swap.asset {
+                Asset::Cw20 { contract_addr } if *contract_addr == info.sender.as_str() => {}
+                _ => {
+                    return Err(ContractError::InvalidAsset {
+                        msg: format!("Unexpected token contract {}", info.sender),
+                    })
+                }
+            }
+
+            // Validate amount matches
+            if wrapper.amount != swap.amount {
+                return Err(ContractError::InsufficientFunds {
+                    required: swap.amount.to_string(),
+                    got: wrapper.amount.to_string(),
+                });
+            }
+
+            let funder = deps.api.addr_validate(&wrapper.sender)?;
+            fund_escrow(deps, env, swap, funder, wrapper.amount)
+        }
+    }
+}
+
+fn fund_escrow(
+    deps: DepsMut,
+    env: Env,
+    mut swap: Escrow,
+    funder: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    // Check if swap is in pending status
+    if swap.status != SwapStatus::Pending {
+        return Err(ContractError::SwapAlreadyCompleted);
+    }
+
+    // Funding is only accepted before the cancellation stage
+    if env.block.time.seconds() >= swap.timelocks.cancellation {
+        return Err(ContractError::TimelockExpired);
+    }
+
+    // Source escrows are funded for their maker, who gets any refund, possibly by a contract
+    // holding the maker's funds; the funder of a destination escrow becomes its resolver unless
+    // the escrow already names one
+    if swap.mode == EscrowMode::Destination {
+        if swap.resolver.as_ref().is_some_and(|resolver| *resolver != funder) {
+            return Err(ContractError::Unauthorized {
+                msg: "Only the resolver can fund this escrow".to_string(),
+            });
+        }
+        swap.resolver = Some(funder.clone());
+    }
 
     // Update swap status
     swap.status = SwapStatus::Funded;
-    swap.resolver = Some(info.sender.clone());
     swap.funded_at = Some(env.block.time);
 
     ESCROW.save(deps.storage, &swap)?;
 
     Ok(Response::new()
+        .add_submessages(factory_callback(&swap, None)?)
         .add_attribute("method", "lock_funds")
-        .add_attribute("resolver", info.sender)
+        .add_attribute("funder", funder)
         .add_attribute("amount", amount))
 }
 
 fn reveal_secret(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     secret: Vec<u8>,
+    merkle_proof: Option<MerkleProof>,
 ) -> Result<Response, ContractError> {
     let mut swap = ESCROW.load(deps.storage)?;
 
@@ -146,43 +350,128 @@ fn reveal_secret(
         return Err(ContractError::SwapNotFunded);
     }
 
-    // Check timelock
-    if env.block.time.seconds() > swap.timelock {
-        return Err(ContractError::TimelockExpired);
+    // Check withdrawal window
+    match swap.timelocks.stage(env.block.time.seconds()) {
+        TimelockStage::FinalityLock => {
+            return Err(ContractError::InvalidStage { stage: "finality_lock".to_string() });
+        }
+        TimelockStage::ResolverWithdrawal => {
+            if swap.resolver.as_ref() != Some(&info.sender) {
+                return Err(ContractError::Unauthorized {
+                    msg: "Only the resolver can withdraw before the public withdrawal stage".to_string(),
+                });
+            }
+        }
+        TimelockStage::PublicWithdrawal => {}
+        TimelockStage::ResolverCancellation | TimelockStage::PublicCancellation => {
+            return Err(ContractError::TimelockExpired);
+        }
     }
 
     // Verify secret
-    let secret_hash = Sha256::digest(&secret).to_vec();
-    if secret_hash != swap.hashlock {
-        return Err(ContractError::InvalidSecret);
+    if secret.len() != SECRET_LENGTH {
+        return Err(ContractError::InvalidSecretLength {
+            expected: SECRET_LENGTH,
+            got: secret.len(),
+        });
     }
+    let secret_hash = swap.hash_algorithm.digest(&secret);
+
+    // Work out how much of the swap this secret releases
+    let (index, release) = match (swap.parts, merkle_proof) {
+        (None, None) => {
+            if secret_hash != swap.hashlock {
+                return Err(ContractError::InvalidSecret);
+            }
+            (0, swap.amount)
+        }
+        (Some(parts), Some(proof)) => {
+            if proof.index > parts || swap.used_secret_indices.contains(&proof.index) {
+                return Err(ContractError::InvalidSecretIndex { index: proof.index });
+            }
+            if !verify_merkle_proof(&swap.hashlock, &merkle_leaf(proof.index, &secret_hash), &proof.proof) {
+                return Err(ContractError::InvalidMerkleProof);
+            }
+
+            // Secret `i < parts` unlocks a cumulative fill of (i + 1) / parts, kept short of the
+            // full amount; only the extra last secret `parts` completes the swap
+            let filled_target = if proof.index == parts {
+                swap.amount
+            } else {
+                swap.amount
+                    .multiply_ratio(proof.index + 1, parts)
+                    .min(swap.amount - Uint128::one())
+            };
+            if filled_target <= swap.filled_amount {
+                return Err(ContractError::InvalidSecretIndex { index: proof.index });
+            }
+            (proof.index, filled_target - swap.filled_amount)
+        }
+        (None, Some(_)) | (Some(_), None) => return Err(ContractError::InvalidMerkleProof),
+    };
 
-    // Update swap status
-    swap.status = SwapStatus::Completed;
-    swap.completed_at = Some(env.block.time);
+    // Update swap status, keeping the secret for the counterparty leg
+    let revealed = RevealedSecret {
+        index,
+        secret,
+        secret_hash,
+        revealed_at: env.block.time,
+    };
+    swap.filled_amount += release;
+    swap.used_secret_indices.push(index);
+    swap.revealed_secrets.push(revealed.clone());
+    let completed = swap.filled_amount == swap.amount;
+    if completed {
+        swap.status = SwapStatus::Completed;
+        swap.completed_at = Some(env.block.time);
+    }
 
     ESCROW.save(deps.storage, &swap)?;
 
-    // Transfer tokens to maker
-    let transfer_msg = BankMsg::Send {
-        to_address: swap.maker.to_string(),
-        amount: vec![cosmwasm_std::Coin {
-            denom: swap.denom,
-            amount: swap.amount,
-        }],
-    };
+    // Transfer the released tokens to the maker (destination) or resolver (source)
+    let recipient = swap
+        .withdrawal_recipient()
+        .ok_or_else(|| StdError::generic_err("Escrow has no withdrawal recipient"))?
+        .clone();
+    let mut messages = vec![transfer_asset(&swap.asset, &recipient, release)?];
+
+    // Safety deposit goes to whoever completed the swap
+    if let Some(deposit) = swap.safety_deposit.clone().filter(|_| completed) {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![deposit],
+            }
+            .into(),
+        );
+    }
+
+    let mut event = Event::new("secret_revealed")
+        .add_attribute("hashlock", hex::encode(&swap.hashlock))
+        .add_attribute("secret_hash", hex::encode(&revealed.secret_hash))
+        .add_attribute("secret", hex::encode(&revealed.secret))
+        .add_attribute("secret_index", index.to_string());
+    // Attribute values cannot be empty, so standalone escrows leave the swap hash out
+    if let Some(swap_hash) = &swap.swap_hash {
+        event = event.add_attribute("swap_hash", swap_hash);
+    }
 
     Ok(Response::new()
-        .add_message(transfer_msg)
+        .add_messages(messages)
+        .add_submessages(factory_callback(&swap, Some(revealed))?)
+        .add_event(event)
         .add_attribute("method", "reveal_secret")
-        .add_attribute("maker", swap.maker)
-        .add_attribute("amount", swap.amount))
+        .add_attribute("recipient", recipient)
+        .add_attribute("secret_index", index.to_string())
+        .add_attribute("amount", release)
+        .add_attribute("filled_amount", swap.filled_amount)
+        .add_attribute("completed", completed.to_string()))
 }
 
 fn cancel_swap(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
 ) -> Result<Response, ContractError> {
     let mut swap = ESCROW.load(deps.storage)?;
 
@@ -195,9 +484,17 @@ fn cancel_swap(
         return Err(ContractError::SwapAlreadyCompleted);
     }
 
-    // Check timelock
-    if env.block.time.seconds() <= swap.timelock {
-        return Err(ContractError::TimelockNotExpired);
+    // Check cancellation window
+    match swap.timelocks.stage(env.block.time.seconds()) {
+        TimelockStage::ResolverCancellation => {
+            if swap.status == SwapStatus::Funded && swap.resolver.as_ref() != Some(&info.sender) {
+                return Err(ContractError::Unauthorized {
+                    msg: "Only the resolver can cancel before the public cancellation stage".to_string(),
+                });
+            }
+        }
+        TimelockStage::PublicCancellation => {}
+        _ => return Err(ContractError::TimelockNotExpired),
     }
 
     // Determine who should receive the funds based on current state
@@ -208,8 +505,8 @@ fn cancel_swap(
             None
         }
         SwapStatus::Funded => {
-            // If funded, return funds to the resolver (who provided the funds)
-            swap.resolver.clone()
+            // If funded, return funds to whoever provided them
+            swap.refund_recipient().cloned()
         }
         SwapStatus::Completed | SwapStatus::Cancelled => {
             // These cases are already handled above
@@ -223,36 +520,118 @@ fn cancel_swap(
 
     // Return funds to appropriate party
     if let Some(recipient) = refund_recipient.clone() {
-        let transfer_msg = BankMsg::Send {
-            to_address: recipient.to_string(),
-            amount: vec![cosmwasm_std::Coin {
-                denom: swap.denom.clone(),
-                amount: swap.amount,
-            }],
-        };
-        messages.push(cosmwasm_std::CosmosMsg::Bank(transfer_msg));
+        messages.push(transfer_asset(&swap.asset, &recipient, swap.amount - swap.filled_amount)?);
+
+        // Safety deposit goes to whoever cancelled the swap
+        if let Some(deposit) = swap.safety_deposit.clone() {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![deposit],
+            }));
+        }
     }
 
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(factory_callback(&swap, None)?)
         .add_attribute("method", "cancel_swap")
         .add_attribute("status", "cancelled")
         .add_attribute("refund_recipient", refund_recipient.map_or("none".to_string(), |addr| addr.to_string())))
 }
 
+// ================================================================================================
+// FACTORY CALLBACKS
+// ================================================================================================
+
+/// Status update for the factory that created the escrow, if any. Sent as a submessage that
+/// only replies on error so a misbehaving factory cannot lock funds in the escrow.
+fn factory_callback(swap: &Escrow, secret: Option<RevealedSecret>) -> StdResult<Option<SubMsg>> {
+    let (Some(factory), Some(swap_hash)) = (&swap.factory, &swap.swap_hash) else {
+        return Ok(None);
+    };
+    let msg = WasmMsg::Execute {
+        contract_addr: factory.to_string(),
+        msg: to_json_binary(&FactoryExecuteMsg::UpdateEscrowStatus {
+            swap_hash: swap_hash.clone(),
+            status: swap.status.clone(),
+            resolver: swap.resolver.as_ref().map(Addr::to_string),
+            filled_amount: swap.filled_amount,
+            secret,
+        })?,
+        funds: vec![],
+    };
+    Ok(Some(SubMsg::reply_on_error(msg, FACTORY_CALLBACK_REPLY_ID)))
+}
+
+// ================================================================================================
+// TRANSFER HELPERS
+// ================================================================================================
+
+fn transfer_asset(asset: &Asset, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match asset {
+        Asset::Native { denom } => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin { denom: denom.clone(), amount }],
+        }
+        .into(),
+        Asset::Cw20 { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.clone(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+// ================================================================================================
+// MERKLE HELPERS
+// ================================================================================================
+
+/// Leaf for secret `index`: keccak256(uint64 index ++ secret hash), as in Fusion+ multiple fills.
+fn merkle_leaf(index: u32, secret_hash: &[u8]) -> Vec<u8> {
+    let mut hasher = Keccak256::new();
+    hasher.update(u64::from(index).to_be_bytes());
+    hasher.update(secret_hash);
+    hasher.finalize().to_vec()
+}
+
+/// Verifies a proof built with sorted-pair keccak256 hashing (OpenZeppelin `MerkleProof`).
+fn verify_merkle_proof(root: &[u8], leaf: &[u8], proof: &[Vec<u8>]) -> bool {
+    let computed = proof.iter().fold(leaf.to_vec(), |node, sibling| {
+        let (left, right) = if node <= *sibling { (&node, sibling) } else { (sibling, &node) };
+        let mut hasher = Keccak256::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().to_vec()
+    });
+    computed == root
+}
+
 // ================================================================================================
 // QUERY FUNCTIONS
 // ================================================================================================
 
-fn query_swap_info(deps: Deps) -> StdResult<SwapInfo> {
+fn query_swap_info(deps: Deps, env: Env) -> StdResult<SwapInfo> {
     let swap = ESCROW.load(deps.storage)?;
+    let stage = swap.timelocks.stage(env.block.time.seconds());
     Ok(SwapInfo {
+        mode: swap.mode,
         maker: swap.maker,
         resolver: swap.resolver,
         amount: swap.amount,
-        denom: swap.denom,
+        asset: swap.asset,
+        safety_deposit: swap.safety_deposit,
         hashlock: swap.hashlock,
-        timelock: swap.timelock,
+        hash_algorithm: swap.hash_algorithm,
+        parts: swap.parts,
+        filled_amount: swap.filled_amount,
+        used_secret_indices: swap.used_secret_indices,
+        revealed_secrets: swap.revealed_secrets,
+        timelocks: swap.timelocks,
+        stage,
         status: swap.status,
         created_at: swap.created_at,
         funded_at: swap.funded_at,
@@ -21,15 +21,42 @@ pub enum ContractError {
     #[error("Invalid denom")]
     InvalidDenom,
 
+    #[error("Invalid asset: {msg}")]
+    InvalidAsset { msg: String },
+
     #[error("Invalid secret")]
     InvalidSecret,
 
+    #[error("Invalid hashlock: expected {expected} bytes, got {got}")]
+    InvalidHashlock { expected: usize, got: usize },
+
+    #[error("Invalid Merkle proof")]
+    InvalidMerkleProof,
+
+    #[error("Invalid secret index: {index}")]
+    InvalidSecretIndex { index: u32 },
+
+    #[error("Invalid number of parts")]
+    InvalidParts,
+
+    #[error("Invalid secret length: expected {expected} bytes, got {got}")]
+    InvalidSecretLength { expected: usize, got: usize },
+
     #[error("Timelock expired")]
     TimelockExpired,
 
     #[error("Timelock not expired")]
     TimelockNotExpired,
 
+    #[error("Invalid timelocks")]
+    InvalidTimelocks,
+
+    #[error("Action not allowed during {stage} stage")]
+    InvalidStage { stage: String },
+
+    #[error("Escrow address already holds funds")]
+    AddressAlreadyFunded,
+
     #[error("Insufficient funds: required {required}, got {got}")]
     InsufficientFunds { required: String, got: String },
 }
\ No newline at end of file
@@ -1,23 +1,54 @@
-use cosmwasm_std::{Addr, Uint128, Timestamp};
+use cosmwasm_std::{Addr, Coin, Uint128, Timestamp};
 use cw_storage_plus::Item;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::SwapStatus;
+use crate::msg::{Asset, EscrowMode, HashAlgorithm, RevealedSecret, SwapStatus, Timelocks};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Escrow {
+    pub mode: EscrowMode,
     pub maker: Addr,
     pub resolver: Option<Addr>,
     pub amount: Uint128,
-    pub denom: String,
+    pub asset: Asset,
+    pub safety_deposit: Option<Coin>,
     pub hashlock: Vec<u8>,
-    pub timelock: u64,
+    pub hash_algorithm: HashAlgorithm,
+    pub parts: Option<u32>,
+    pub filled_amount: Uint128,
+    pub used_secret_indices: Vec<u32>,
+    pub revealed_secrets: Vec<RevealedSecret>,
+    pub timelocks: Timelocks,
     pub status: SwapStatus,
     pub created_at: Timestamp,
     pub funded_at: Option<Timestamp>,
     pub completed_at: Option<Timestamp>,
+    /// Factory notified of status changes, with the swap hash it registered the escrow under
+    pub factory: Option<Addr>,
+    pub swap_hash: Option<String>,
 }
 
+impl Escrow {
+    /// Party paid when a secret is revealed.
+    pub fn withdrawal_recipient(&self) -> Option<&Addr> {
+        match self.mode {
+            EscrowMode::Destination => Some(&self.maker),
+            EscrowMode::Source => self.resolver.as_ref(),
+        }
+    }
+
+    /// Party refunded when a funded escrow is cancelled.
+    pub fn refund_recipient(&self) -> Option<&Addr> {
+        match self.mode {
+            EscrowMode::Destination => self.resolver.as_ref(),
+            EscrowMode::Source => Some(&self.maker),
+        }
+    }
+}
+
+// Reply id for factory status callbacks
+pub const FACTORY_CALLBACK_REPLY_ID: u64 = 1;
+
 // Storage - single escrow per contract
 pub const ESCROW: Item<Escrow> = Item::new("swap");
\ No newline at end of file
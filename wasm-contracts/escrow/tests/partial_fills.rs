@@ -0,0 +1,290 @@
+//! Partial fills of a factory-created destination escrow through Merkle-tree secrets.
+
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{coin, Addr, Empty, Uint128};
+use cw_multi_test::addons::{MockAddressGenerator, MockApiBech32};
+use cw_multi_test::{
+    App, AppBuilder, AppResponse, BankKeeper, ContractWrapper, DistributionKeeper, Executor,
+    FailingModule, GovFailingModule, IbcFailingModule, StakeKeeper, StargateFailing,
+    WasmKeeper,
+};
+use escrow::msg::{
+    Asset, ExecuteMsg, HashAlgorithm, MerkleProof, QueryMsg, SwapInfo, SwapStatus, Timelocks,
+};
+use escrow::ContractError;
+use htlc_msgs::factory::{
+    ExecuteMsg as FactoryExecuteMsg, HTLCInfo, InstantiateMsg as FactoryInstantiateMsg,
+    QueryMsg as FactoryQueryMsg,
+};
+use sha3::{Digest, Keccak256};
+
+type TestApp = App<
+    BankKeeper,
+    MockApiBech32,
+    MockStorage,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    StargateFailing,
+>;
+
+const DENOM: &str = "uosmo";
+const SWAP_HASH: &str = "swap-1";
+const AMOUNT: u128 = 1_000;
+const DEPOSIT: u128 = 100;
+const PARTS: u32 = 4;
+
+struct Suite {
+    app: TestApp,
+    maker: Addr,
+    resolver: Addr,
+    escrow: Addr,
+    /// `PARTS + 1` secrets, the leaves of the hashlock Merkle tree
+    secrets: Vec<Vec<u8>>,
+    tree: Vec<Vec<Vec<u8>>>,
+}
+
+impl Suite {
+    /// Destination escrow of `AMOUNT` in `PARTS` parts, created and funded by the resolver.
+    fn new() -> Self {
+        let api = MockApiBech32::new("osmo");
+        let admin = api.addr_make("admin");
+        let maker = api.addr_make("maker");
+        let resolver = api.addr_make("resolver");
+
+        let mut app = AppBuilder::new()
+            .with_api(api)
+            .with_wasm(WasmKeeper::new().with_address_generator(MockAddressGenerator))
+            .build(|router, _, storage| {
+                router.bank.init_balance(storage, &resolver, vec![coin(10_000, DENOM)]).unwrap();
+            });
+        let escrow_code = app.store_code(Box::new(
+            ContractWrapper::new(escrow::contract::execute, escrow::contract::instantiate, escrow::contract::query)
+                .with_reply(escrow::contract::reply),
+        ));
+        let factory_code = app.store_code(Box::new(
+            ContractWrapper::new(
+                escrow_factory::contract::execute,
+                escrow_factory::contract::instantiate,
+                escrow_factory::contract::query,
+            )
+            .with_reply(escrow_factory::contract::reply),
+        ));
+        let factory = app
+            .instantiate_contract(
+                factory_code,
+                admin.clone(),
+                &FactoryInstantiateMsg { admin: admin.to_string(), htlc_code_id: escrow_code },
+                &[],
+                "factory",
+                None,
+            )
+            .unwrap();
+
+        let secrets: Vec<Vec<u8>> = (0..=PARTS).map(|i| vec![i as u8 + 1; 32]).collect();
+        let leaves = secrets
+            .iter()
+            .enumerate()
+            .map(|(index, secret)| leaf(index as u32, &HashAlgorithm::Sha256.digest(secret)))
+            .collect();
+        let tree = build_tree(leaves);
+
+        let now = app.block_info().time.seconds();
+        let msg = FactoryExecuteMsg::CreateFundedHTLC {
+            swap_hash: SWAP_HASH.to_string(),
+            maker: maker.to_string(),
+            amount: Uint128::new(AMOUNT),
+            asset: Asset::Native { denom: DENOM.to_string() },
+            hashlock: tree.last().unwrap()[0].clone(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            parts: Some(PARTS),
+            timelocks: Timelocks {
+                withdrawal: now,
+                public_withdrawal: now + 100,
+                cancellation: now + 200,
+                public_cancellation: now + 300,
+            },
+            safety_deposit: Some(coin(DEPOSIT, DENOM)),
+        };
+        app.execute_contract(resolver.clone(), factory.clone(), &msg, &[coin(AMOUNT + DEPOSIT, DENOM)])
+            .unwrap();
+        let htlc: HTLCInfo = app
+            .wrap()
+            .query_wasm_smart(&factory, &FactoryQueryMsg::GetHTLC { swap_hash: SWAP_HASH.to_string() })
+            .unwrap();
+        let escrow = htlc.htlc_address;
+
+        Suite { app, maker, resolver, escrow, secrets, tree }
+    }
+
+    fn proof(&self, index: u32) -> MerkleProof {
+        let mut position = index as usize;
+        let mut proof = vec![];
+        for level in &self.tree[..self.tree.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                proof.push(sibling.clone());
+            }
+            position /= 2;
+        }
+        MerkleProof { index, proof }
+    }
+
+    fn reveal(&mut self, index: u32) -> anyhow::Result<AppResponse> {
+        let secret = self.secrets[index as usize].clone();
+        let proof = self.proof(index);
+        self.reveal_with(secret, proof)
+    }
+
+    fn reveal_with(&mut self, secret: Vec<u8>, proof: MerkleProof) -> anyhow::Result<AppResponse> {
+        self.app.execute_contract(
+            self.resolver.clone(),
+            self.escrow.clone(),
+            &ExecuteMsg::RevealSecret { secret, merkle_proof: Some(proof) },
+            &[],
+        )
+    }
+
+    fn swap_info(&self) -> SwapInfo {
+        self.app.wrap().query_wasm_smart(&self.escrow, &QueryMsg::GetSwapInfo {}).unwrap()
+    }
+
+    fn balance(&self, addr: &Addr) -> u128 {
+        self.app.wrap().query_balance(addr, DENOM).unwrap().amount.u128()
+    }
+}
+
+fn leaf(index: u32, secret_hash: &[u8]) -> Vec<u8> {
+    let mut hasher = Keccak256::new();
+    hasher.update(u64::from(index).to_be_bytes());
+    hasher.update(secret_hash);
+    hasher.finalize().to_vec()
+}
+
+/// Sorted-pair keccak256 tree, bottom level first; an unpaired node moves up unchanged.
+fn build_tree(leaves: Vec<Vec<u8>>) -> Vec<Vec<Vec<u8>>> {
+    let mut tree = vec![leaves];
+    while tree.last().unwrap().len() > 1 {
+        let level = tree
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let (left, right) = if left <= right { (left, right) } else { (right, left) };
+                    let mut hasher = Keccak256::new();
+                    hasher.update(left);
+                    hasher.update(right);
+                    hasher.finalize().to_vec()
+                }
+                [node] => node.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        tree.push(level);
+    }
+    tree
+}
+
+fn contract_error(err: anyhow::Error) -> ContractError {
+    err.downcast().unwrap()
+}
+
+#[test]
+fn secrets_must_be_proven_against_the_hashlock_root() {
+    let mut suite = Suite::new();
+
+    // A proof for another index
+    let secret = suite.secrets[1].clone();
+    let mut proof = suite.proof(2);
+    proof.index = 1;
+    let err = suite.reveal_with(secret, proof).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidMerkleProof);
+
+    // A secret that is not a leaf
+    let proof = suite.proof(1);
+    let err = suite.reveal_with(vec![0xff; 32], proof).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidMerkleProof);
+
+    // Partial-fill escrows need a proof
+    let secret = suite.secrets[0].clone();
+    let err = suite
+        .app
+        .execute_contract(
+            suite.resolver.clone(),
+            suite.escrow.clone(),
+            &ExecuteMsg::RevealSecret { secret, merkle_proof: None },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidMerkleProof);
+
+    assert_eq!(suite.swap_info().filled_amount, Uint128::zero());
+}
+
+#[test]
+fn each_secret_releases_its_cumulative_fill() {
+    let mut suite = Suite::new();
+
+    // The first secret already fills one part
+    suite.reveal(0).unwrap();
+    assert_eq!(suite.balance(&suite.maker), 250);
+
+    // Skipping ahead releases everything up to three parts
+    suite.reveal(2).unwrap();
+    assert_eq!(suite.balance(&suite.maker), 750);
+
+    // The last part's secret stops short of the full amount
+    suite.reveal(3).unwrap();
+    assert_eq!(suite.balance(&suite.maker), 999);
+
+    let info = suite.swap_info();
+    assert_eq!(info.filled_amount, Uint128::new(999));
+    assert_eq!(info.used_secret_indices, vec![0, 2, 3]);
+    assert_eq!(info.status, SwapStatus::Funded);
+}
+
+#[test]
+fn secret_indices_cannot_be_reused_or_go_backwards() {
+    let mut suite = Suite::new();
+    suite.reveal(1).unwrap();
+
+    let err = suite.reveal(1).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidSecretIndex { index: 1 });
+
+    // An earlier index would release nothing
+    let err = suite.reveal(0).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidSecretIndex { index: 0 });
+
+    // There is no secret past the last one
+    let secret = suite.secrets[PARTS as usize].clone();
+    let proof = MerkleProof { index: PARTS + 1, ..suite.proof(PARTS) };
+    let err = suite.reveal_with(secret, proof).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidSecretIndex { index: PARTS + 1 });
+
+    assert_eq!(suite.balance(&suite.maker), 500);
+}
+
+#[test]
+fn the_last_secret_completes_the_swap() {
+    let mut suite = Suite::new();
+    suite.reveal(3).unwrap();
+    assert_eq!(suite.swap_info().status, SwapStatus::Funded);
+
+    let resolver_balance = suite.balance(&suite.resolver);
+    suite.reveal(PARTS).unwrap();
+
+    let info = suite.swap_info();
+    assert_eq!(info.filled_amount, Uint128::new(AMOUNT));
+    assert_eq!(info.status, SwapStatus::Completed);
+    assert_eq!(suite.balance(&suite.maker), AMOUNT);
+    // The safety deposit goes to whoever completed the swap
+    assert_eq!(suite.balance(&suite.resolver), resolver_balance + DEPOSIT);
+    assert_eq!(suite.balance(&suite.escrow), 0);
+
+    // Nothing is left to reveal
+    let err = suite.reveal(2).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::SwapNotFunded);
+}
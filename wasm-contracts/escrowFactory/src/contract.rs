@@ -1,13 +1,33 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Addr, Uint128, WasmMsg, SubMsg, Reply, SubMsgResult, Order, StdError,
+    instantiate2_address, to_json_binary, to_json_vec, Binary, Deps, DepsMut, Env, MessageInfo,
+    Response, StdResult, Addr, Coin, Uint128, WasmMsg, SubMsg, Reply, StdError, Order,
 };
+use cw_storage_plus::Bound;
+use cw_utils::{one_coin, parse_reply_instantiate_data, NativeBalance};
+use htlc_msgs::escrow::{InstantiateMsg as EscrowInstantiateMsg, MERKLE_ROOT_LENGTH};
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, HTLCInfo as EscrowInfoMsg, ConfigResponse};
-use crate::state::{Config, EscrowInfo as EscrowInfoState, CONFIG, ESCROWS, MAKER_ESCROWS};
 
-#[entry_point]
+// Pagination settings for list queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
+/// Reply id of secret hook calls, outside the ids handed out to instantiations
+const SECRET_HOOK_REPLY_ID: u64 = u64::MAX;
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, QueryMsg, HTLCInfo as EscrowInfoMsg, ConfigResponse, Timelocks,
+    HashAlgorithm, Asset, EscrowMode, EscrowAddressResponse, EscrowImmutables, SwapStatus, ExpiryCursor,
+    RevealedSecret, SecretHookMsg, SecretResponse,
+};
+use crate::state::{
+    status_key, Config, EscrowInfo as EscrowInfoState, SecretInfo, CONFIG, ESCROWS, NEXT_REPLY_ID,
+    PENDING_INSTANTIATIONS, SECRETS,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
@@ -17,6 +37,7 @@ pub fn instantiate(
     let config = Config {
         admin: deps.api.addr_validate(&msg.admin)?,
         htlc_code_id: msg.htlc_code_id,
+        secret_hook: None,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -27,7 +48,7 @@ pub fn instantiate(
         .add_attribute("htlc_code_id", config.htlc_code_id.to_string()))
 }
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
@@ -38,16 +59,156 @@ pub fn execute(
         ExecuteMsg::CreateHTLC {
             swap_hash,
             maker,
+            resolver,
+            amount,
+            asset,
+            hashlock,
+            hash_algorithm,
+            parts,
+            timelocks,
+            safety_deposit,
+        } => create_htlc(deps, env, info, NewEscrow {
+            swap_hash,
+            mode: EscrowMode::Destination,
+            maker,
+            resolver,
+            amount,
+            asset,
+            hashlock,
+            hash_algorithm,
+            parts,
+            timelocks,
+            safety_deposit,
+            funds: vec![],
+        }),
+
+        ExecuteMsg::CreateFundedHTLC {
+            swap_hash,
+            maker,
+            amount,
+            asset,
+            hashlock,
+            hash_algorithm,
+            parts,
+            timelocks,
+            safety_deposit,
+        } => {
+            let Asset::Native { denom } = &asset else {
+                return Err(ContractError::InvalidAsset {
+                    msg: "Only native assets can be funded through the factory".to_string(),
+                });
+            };
+
+            // Validate payment: the swap amount in the asset's denom and the safety deposit, which
+            // may be in another denom, each sent exactly
+            let mut required = NativeBalance(vec![Coin::new(amount.u128(), denom)]);
+            if let Some(deposit) = &safety_deposit {
+                required += deposit.clone();
+            }
+            required.normalize();
+            let mut paid = NativeBalance(info.funds.clone());
+            paid.normalize();
+            for coin in &required.0 {
+                let got = paid.0.iter().find(|paid| paid.denom == coin.denom);
+                if got.map(|paid| paid.amount) != Some(coin.amount) {
+                    return Err(ContractError::InsufficientFunds {
+                        required: coin.to_string(),
+                        got: got.map_or_else(|| Coin::new(0, &coin.denom), Coin::clone).to_string(),
+                    });
+                }
+            }
+            if paid.0.len() != required.0.len() {
+                return Err(ContractError::InsufficientFunds {
+                    required: required.to_string(),
+                    got: paid.to_string(),
+                });
+            }
+
+            let resolver = Some(info.sender.to_string());
+            let funds = info.funds.clone();
+            create_htlc(deps, env, info, NewEscrow {
+                swap_hash,
+                mode: EscrowMode::Destination,
+                maker,
+                resolver,
+                amount,
+                asset,
+                hashlock,
+                hash_algorithm,
+                parts,
+                timelocks,
+                safety_deposit,
+                funds,
+            })
+        }
+
+        ExecuteMsg::CreateHTLCFromTransfer {
+            swap_hash,
+            maker,
+            resolver,
+            hashlock,
+            hash_algorithm,
+            parts,
+            timelocks,
+        } => {
+            // ICS-20 carries a single coin, so there is no separate safety deposit
+            let coin = one_coin(&info)?;
+            create_htlc(deps, env, info, NewEscrow {
+                swap_hash,
+                mode: EscrowMode::Destination,
+                maker,
+                resolver: Some(resolver),
+                amount: coin.amount,
+                asset: Asset::Native { denom: coin.denom.clone() },
+                hashlock,
+                hash_algorithm,
+                parts,
+                timelocks,
+                safety_deposit: None,
+                funds: vec![coin],
+            })
+        }
+
+        ExecuteMsg::CreateSrcEscrow {
+            swap_hash,
+            maker,
+            resolver,
+            amount,
+            asset,
+            hashlock,
+            hash_algorithm,
+            parts,
+            timelocks,
+            safety_deposit,
+        } => create_htlc(deps, env, info, NewEscrow {
+            swap_hash,
+            mode: EscrowMode::Source,
+            maker,
+            resolver: Some(resolver),
             amount,
-            denom,
+            asset,
             hashlock,
-            timelock,
-        } => create_htlc(deps, env, info, swap_hash, maker, amount, denom, hashlock, timelock),
+            hash_algorithm,
+            parts,
+            timelocks,
+            safety_deposit,
+            funds: vec![],
+        }),
         
+        ExecuteMsg::UpdateEscrowStatus { swap_hash, status, resolver, filled_amount, secret } => {
+            update_escrow_status(deps, env, info, swap_hash, EscrowUpdate {
+                status,
+                resolver,
+                filled_amount,
+                secret,
+            })
+        }
+
         ExecuteMsg::UpdateHTLCCodeId { code_id } => {
             update_htlc_code_id(deps, info, code_id)
         }
         
+        ExecuteMsg::UpdateSecretHook { hook } => update_secret_hook(deps, info, hook),
         ExecuteMsg::UpdateAdmin { admin } => update_admin(deps, info, admin),
     }
 }
@@ -56,62 +217,66 @@ pub fn execute(
 // REPLY HANDLER
 // ================================================================================================
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
-    match msg.result {
-        SubMsgResult::Ok(res) => {
-            // Find instantiated contract address from events
-            let mut contract_addr: Option<String> = None;
-            for event in res.events {
-                if event.ty == "instantiate" || event.ty == "wasm" {
-                    for attr in event.attributes {
-                        if attr.key == "_contract_address" || attr.key == "contract_address" {
-                            contract_addr = Some(attr.value);
-                            break;
-                        }
-                    }
-                }
-                if contract_addr.is_some() {
-                    break;
-                }
-            }
+    // A failing secret hook must not undo the escrow's status report
+    if msg.id == SECRET_HOOK_REPLY_ID {
+        let error = msg.result.into_result().err().unwrap_or_default();
+        return Ok(Response::new()
+            .add_attribute("method", "secret_hook_failed")
+            .add_attribute("error", error));
+    }
 
-            let htlc_address = Addr::unchecked(
-                contract_addr.ok_or_else(|| StdError::generic_err("No _contract_address in reply"))?,
-            );
-
-            // Update the most recent pending HTLC (with empty address)
-            let mut pending_key: Option<String> = None;
-            let mut pending_info: Option<EscrowInfoState> = None;
-            for item in ESCROWS.range(deps.storage, None, None, Order::Descending) {
-                let (key, info) = item.map_err(|e| StdError::generic_err(e.to_string()))?;
-                if info.htlc_address == Addr::unchecked("") {
-                    pending_key = Some(key);
-                    pending_info = Some(info);
-                    break;
-                }
-            }
+    // Each instantiation gets its own reply id, so batched creations can't be confused
+    let swap_hash = PENDING_INSTANTIATIONS
+        .may_load(deps.storage, msg.id)?
+        .ok_or(ContractError::UnknownReplyId { id: msg.id })?;
+    PENDING_INSTANTIATIONS.remove(deps.storage, msg.id);
 
-            let key = pending_key.ok_or_else(|| StdError::generic_err("No pending HTLC to update"))?;
-            let mut info = pending_info.ok_or_else(|| StdError::generic_err("No pending HTLC info"))?;
-            info.htlc_address = htlc_address.clone();
-            ESCROWS.save(deps.storage, key.clone(), &info)?;
+    let res = parse_reply_instantiate_data(msg)?;
+    let info = ESCROWS.load(deps.storage, swap_hash.clone())?;
 
-            Ok(Response::new()
-                .add_attribute("method", "reply")
-                .add_attribute("htlc_address", htlc_address)
-                .add_attribute("swap_hash", info.swap_hash))
-        }
-        SubMsgResult::Err(err) => Err(ContractError::Std(StdError::generic_err(err))),
+    // The address was predicted at creation; make sure the escrow landed there
+    if res.contract_address != info.htlc_address.as_str() {
+        return Err(ContractError::UnexpectedHTLCAddress {
+            swap_hash,
+            expected: info.htlc_address.to_string(),
+            got: res.contract_address,
+        });
     }
+
+    Ok(Response::new()
+        .add_attribute("method", "reply")
+        .add_attribute("htlc_address", info.htlc_address)
+        .add_attribute("swap_hash", swap_hash))
 }
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetHTLC { swap_hash } => to_json_binary(&query_htlc(deps, swap_hash)?),
-        QueryMsg::GetHTLCsByMaker { maker } => to_json_binary(&query_htlcs_by_maker(deps, maker)?),
+        QueryMsg::ListHTLCs { status, start_after, limit } => {
+            to_json_binary(&query_list_htlcs(deps, status, start_after, limit)?)
+        }
+        QueryMsg::GetHTLCsByMaker { maker, status, start_after, limit } => {
+            to_json_binary(&query_htlcs_by_maker(deps, maker, status, start_after, limit)?)
+        }
+        QueryMsg::GetHTLCsByResolver { resolver, status, start_after, limit } => {
+            to_json_binary(&query_htlcs_by_resolver(deps, resolver, status, start_after, limit)?)
+        }
+        QueryMsg::GetHTLCsByStatus { status, start_after, limit } => {
+            to_json_binary(&query_htlcs_by_status(deps, status, start_after, limit)?)
+        }
+        QueryMsg::GetHTLCsByExpiry { expires_before, status, start_after, limit } => {
+            to_json_binary(&query_htlcs_by_expiry(deps, expires_before, status, start_after, limit)?)
+        }
+        QueryMsg::GetSecretByHashlock { hashlock } => {
+            to_json_binary(&query_secret_by_hashlock(deps, hashlock)?)
+        }
         QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::PredictEscrowAddress { immutables } => {
+            to_json_binary(&query_predict_escrow_address(deps, env, immutables)?)
+        }
     }
 }
 
@@ -119,88 +284,311 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
 // EXECUTE FUNCTIONS
 // ================================================================================================
 
-fn create_htlc(
-    deps: DepsMut,
-    env: Env,
-    _info: MessageInfo,
+/// Parameters shared by every escrow the factory instantiates.
+struct NewEscrow {
     swap_hash: String,
+    mode: EscrowMode,
     maker: String,
+    resolver: Option<String>,
     amount: Uint128,
-    denom: String,
+    asset: Asset,
     hashlock: Vec<u8>,
-    timelock: u64,
+    hash_algorithm: HashAlgorithm,
+    parts: Option<u32>,
+    timelocks: Timelocks,
+    safety_deposit: Option<Coin>,
+    /// Forwarded to the escrow, which starts out `Funded` when non-empty
+    funds: Vec<Coin>,
+}
+
+fn create_htlc(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    escrow: NewEscrow,
 ) -> Result<Response, ContractError> {
     // Check if HTLC already exists
-    if ESCROWS.has(deps.storage, swap_hash.clone()) {
-        return Err(ContractError::HTLCAlreadyExists { swap_hash });
-    }
-
-    // Validate timelock
-    if timelock <= env.block.time.seconds() {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Timelock expired")));
-    }
-
-    // Validate amount
-    if amount.is_zero() {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Invalid amount")));
+    if ESCROWS.has(deps.storage, escrow.swap_hash.clone()) {
+        return Err(ContractError::HTLCAlreadyExists { swap_hash: escrow.swap_hash });
     }
 
-    // Validate denom
-    if denom.is_empty() {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Invalid denom")));
-    }
+    let asset = validate_escrow(deps.as_ref(), &env, &escrow)?;
+    let NewEscrow {
+        swap_hash,
+        mode,
+        maker,
+        resolver,
+        amount,
+        asset: _,
+        hashlock,
+        hash_algorithm,
+        parts,
+        timelocks,
+        safety_deposit,
+        funds,
+    } = escrow;
 
     let maker_addr = deps.api.addr_validate(&maker)?;
+    let resolver_addr = resolver.as_deref().map(|r| deps.api.addr_validate(r)).transpose()?;
     let config = CONFIG.load(deps.storage)?;
+    let status = if funds.is_empty() { SwapStatus::Pending } else { SwapStatus::Funded };
 
     // Create HTLC contract instantiation message
-    let htlc_init_msg = serde_json::json!({
-        "admin": config.admin.to_string(),
-        "maker": maker.clone(),
-        "amount": amount.to_string(),
-        "denom": denom.clone(),
-        "hashlock": hashlock.clone(),
-        "timelock": timelock
-    });
-
-    let instantiate_msg = WasmMsg::Instantiate {
+    let htlc_init_msg = EscrowInstantiateMsg {
+        admin: config.admin.to_string(),
+        mode: mode.clone(),
+        maker: maker.clone(),
+        resolver,
+        amount,
+        asset: asset.clone(),
+        hashlock: hashlock.clone(),
+        hash_algorithm: hash_algorithm.clone(),
+        parts,
+        timelocks: timelocks.clone(),
+        safety_deposit: safety_deposit.clone(),
+        swap_hash: Some(swap_hash.clone()),
+    };
+
+    // Deterministic address derived from every immutable, so it can't be taken by an escrow
+    // with other terms; the escrow refuses to start on an address that already holds funds
+    let salt = escrow_salt(&EscrowImmutables {
+        swap_hash: swap_hash.clone(),
+        mode: mode.clone(),
+        maker: maker_addr.to_string(),
+        resolver: resolver_addr.as_ref().map(Addr::to_string),
+        amount,
+        asset: asset.clone(),
+        hashlock: hashlock.clone(),
+        hash_algorithm: hash_algorithm.clone(),
+        parts,
+        timelocks: timelocks.clone(),
+        safety_deposit: safety_deposit.clone(),
+    })?;
+    let htlc_address = predict_escrow_address(deps.as_ref(), &env, &salt)?;
+
+    let instantiate_msg = WasmMsg::Instantiate2 {
         admin: Some(config.admin.to_string()),
         code_id: config.htlc_code_id,
-        msg: to_json_binary(&htlc_init_msg)?,
-        funds: vec![],
         label: format!("htlc-{}", swap_hash),
+        msg: to_json_binary(&htlc_init_msg)?,
+        funds,
+        salt,
     };
 
-    let sub_msg = SubMsg::reply_on_success(instantiate_msg, 1);
+    let reply_id = NEXT_REPLY_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_REPLY_ID.save(deps.storage, &(reply_id + 1))?;
+    PENDING_INSTANTIATIONS.save(deps.storage, reply_id, &swap_hash)?;
+
+    let sub_msg = SubMsg::reply_on_success(instantiate_msg, reply_id);
 
-    // Store HTLC info (will be updated in reply)
+    // Store HTLC info
     let htlc_info = EscrowInfoState {
         swap_hash: swap_hash.clone(),
-        htlc_address: Addr::unchecked(""), // Will be updated in reply
+        htlc_address: htlc_address.clone(),
+        mode,
         maker: maker_addr.clone(),
+        resolver: resolver_addr,
         amount,
-        denom: denom.clone(),
+        asset,
         hashlock,
-        timelock,
+        hash_algorithm,
+        parts,
+        timelocks: timelocks.clone(),
+        safety_deposit,
+        funded_at: (status == SwapStatus::Funded).then_some(env.block.time),
+        status,
+        filled_amount: Uint128::zero(),
         created_at: env.block.time,
+        completed_at: None,
+        cancelled_at: None,
     };
 
     ESCROWS.save(deps.storage, swap_hash.clone(), &htlc_info)?;
 
-    // Add to maker's HTLCs
-    let mut maker_htlcs = MAKER_ESCROWS
-        .may_load(deps.storage, maker_addr.clone())?
-        .unwrap_or_default();
-    maker_htlcs.push(swap_hash.clone());
-    MAKER_ESCROWS.save(deps.storage, maker_addr, &maker_htlcs)?;
-
     Ok(Response::new()
         .add_submessage(sub_msg)
         .add_attribute("method", "create_htlc")
         .add_attribute("swap_hash", swap_hash)
+        .add_attribute("htlc_address", htlc_address)
         .add_attribute("maker", maker)
         .add_attribute("amount", amount)
-        .add_attribute("timelock", timelock.to_string()))
+        .add_attribute("cancellation", timelocks.cancellation.to_string()))
+}
+
+/// Checks the parameters the escrow would reject at instantiation, so a bad request fails
+/// before anything is registered, and returns the asset with its contract address validated.
+fn validate_escrow(deps: Deps, env: &Env, escrow: &NewEscrow) -> Result<Asset, ContractError> {
+    // Validate timelocks: stages must be ordered and cancellation must lie in the future
+    if !escrow.timelocks.is_valid(env.block.time.seconds()) {
+        return Err(ContractError::InvalidTimelocks);
+    }
+
+    // Validate amount
+    if escrow.amount.is_zero() {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    // Validate asset
+    let asset = match &escrow.asset {
+        Asset::Native { denom } => {
+            if denom.is_empty() {
+                return Err(ContractError::InvalidDenom);
+            }
+            escrow.asset.clone()
+        }
+        Asset::Cw20 { contract_addr } => {
+            if escrow.safety_deposit.is_some() {
+                return Err(ContractError::InvalidAsset {
+                    msg: "Safety deposit is not supported for CW20 escrows".to_string(),
+                });
+            }
+            Asset::Cw20 { contract_addr: deps.api.addr_validate(contract_addr)?.to_string() }
+        }
+    };
+
+    // Validate hashlock length: a Merkle root for partial fills, otherwise a secret hash
+    let hashlock_len = match escrow.parts {
+        Some(0) => return Err(ContractError::InvalidParts),
+        Some(_) => MERKLE_ROOT_LENGTH,
+        None => escrow.hash_algorithm.digest_len(),
+    };
+    if escrow.hashlock.len() != hashlock_len {
+        return Err(ContractError::InvalidHashlock {
+            expected: hashlock_len,
+            got: escrow.hashlock.len(),
+        });
+    }
+
+    // Validate safety deposit
+    if let Some(deposit) = &escrow.safety_deposit {
+        if deposit.amount.is_zero() {
+            return Err(ContractError::InvalidAmount);
+        }
+        if deposit.denom.is_empty() {
+            return Err(ContractError::InvalidDenom);
+        }
+    }
+
+    Ok(asset)
+}
+
+/// Status callback payload reported by an escrow.
+struct EscrowUpdate {
+    status: SwapStatus,
+    resolver: Option<String>,
+    filled_amount: Uint128,
+    secret: Option<RevealedSecret>,
+}
+
+fn update_escrow_status(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_hash: String,
+    update: EscrowUpdate,
+) -> Result<Response, ContractError> {
+    let EscrowUpdate { status, resolver, filled_amount, secret } = update;
+    let mut htlc_info = ESCROWS
+        .may_load(deps.storage, swap_hash.clone())?
+        .ok_or_else(|| ContractError::HTLCNotFound { swap_hash: swap_hash.clone() })?;
+
+    // Only the escrow itself may report its status
+    if info.sender != htlc_info.htlc_address {
+        return Err(ContractError::Unauthorized {
+            msg: "Only the escrow can update its status".to_string(),
+        });
+    }
+
+    // Completed and cancelled escrows are final
+    if matches!(htlc_info.status, SwapStatus::Completed | SwapStatus::Cancelled) {
+        return Err(ContractError::InvalidStatusTransition {
+            swap_hash,
+            from: status_key(&htlc_info.status),
+            to: status_key(&status),
+        });
+    }
+
+    if let Some(resolver) = resolver {
+        htlc_info.resolver = Some(deps.api.addr_validate(&resolver)?);
+    }
+    let status_changed = htlc_info.status != status;
+    match status {
+        SwapStatus::Pending => {}
+        SwapStatus::Funded => {
+            htlc_info.funded_at.get_or_insert(env.block.time);
+        }
+        SwapStatus::Completed => htlc_info.completed_at = Some(env.block.time),
+        SwapStatus::Cancelled => htlc_info.cancelled_at = Some(env.block.time),
+    }
+    htlc_info.status = status;
+    htlc_info.filled_amount = filled_amount;
+
+    ESCROWS.save(deps.storage, swap_hash.clone(), &htlc_info)?;
+
+    // Index the revealed secret so the counterparty leg can be completed without replaying txs.
+    // Both legs of a swap reveal the same secret, so the first reveal is kept
+    let mut hook_msgs = vec![];
+    if let Some(secret) = &secret {
+        if !SECRETS.has(deps.storage, &secret.secret_hash) {
+            SECRETS.save(deps.storage, &secret.secret_hash, &SecretInfo {
+                swap_hash: swap_hash.clone(),
+                index: secret.index,
+                secret: secret.secret.clone(),
+                revealed_at: secret.revealed_at,
+            })?;
+        }
+        hook_msgs.push(SecretHookMsg::SecretRevealed {
+            swap_hash: swap_hash.clone(),
+            secret: secret.secret.clone(),
+        });
+    }
+    if status_changed {
+        hook_msgs.push(SecretHookMsg::StatusChanged {
+            swap_hash: swap_hash.clone(),
+            status: htlc_info.status.clone(),
+        });
+    }
+
+    let mut messages = vec![];
+    if let Some(hook) = CONFIG.load(deps.storage)?.secret_hook {
+        for hook_msg in hook_msgs {
+            let hook_msg = WasmMsg::Execute {
+                contract_addr: hook.to_string(),
+                msg: to_json_binary(&hook_msg)?,
+                funds: vec![],
+            };
+            messages.push(SubMsg::reply_on_error(hook_msg, SECRET_HOOK_REPLY_ID));
+        }
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("method", "update_escrow_status")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("status", status_key(&htlc_info.status))
+        .add_attribute("filled_amount", filled_amount))
+}
+
+fn update_secret_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    hook: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Check if sender is admin
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can update the secret hook".to_string()
+        });
+    }
+
+    config.secret_hook = hook.map(|hook| deps.api.addr_validate(&hook)).transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_secret_hook")
+        .add_attribute("secret_hook", config.secret_hook.map(|hook| hook.to_string()).unwrap_or_default()))
 }
 
 fn update_htlc_code_id(
@@ -255,30 +643,155 @@ fn update_admin(
 
 fn query_htlc(deps: Deps, swap_hash: String) -> StdResult<EscrowInfoMsg> {
     let htlc_info = ESCROWS.load(deps.storage, swap_hash)?;
-    Ok(EscrowInfoMsg {
-        swap_hash: htlc_info.swap_hash,
+    Ok(to_htlc_info(htlc_info))
+}
+
+fn query_secret_by_hashlock(deps: Deps, hashlock: Vec<u8>) -> StdResult<SecretResponse> {
+    let secret = SECRETS.load(deps.storage, &hashlock)?;
+    let htlc_info = ESCROWS.load(deps.storage, secret.swap_hash.clone())?;
+    Ok(SecretResponse {
+        swap_hash: secret.swap_hash,
         htlc_address: htlc_info.htlc_address,
-        maker: htlc_info.maker,
-        amount: htlc_info.amount,
-        denom: htlc_info.denom,
-        hashlock: htlc_info.hashlock,
-        timelock: htlc_info.timelock,
-        created_at: htlc_info.created_at,
+        index: secret.index,
+        secret: secret.secret,
+        revealed_at: secret.revealed_at,
     })
 }
 
-fn query_htlcs_by_maker(deps: Deps, maker: String) -> StdResult<Vec<EscrowInfoMsg>> {
+fn query_list_htlcs(
+    deps: Deps,
+    status: Option<SwapStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EscrowInfoMsg>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let items = match status {
+        Some(status) => ESCROWS
+            .idx
+            .status
+            .prefix(status_key(&status))
+            .range(deps.storage, start, None, Order::Ascending),
+        None => ESCROWS.range(deps.storage, start, None, Order::Ascending),
+    };
+    items
+        .take(limit)
+        .map(|item| item.map(|(_, info)| to_htlc_info(info)))
+        .collect()
+}
+
+fn query_htlcs_by_maker(
+    deps: Deps,
+    maker: String,
+    status: Option<SwapStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EscrowInfoMsg>> {
     let maker_addr = deps.api.addr_validate(&maker)?;
-    let swap_hashes = MAKER_ESCROWS.load(deps.storage, maker_addr).unwrap_or_default();
-    
-    let mut htlcs = Vec::new();
-    for swap_hash in swap_hashes {
-        if let Ok(htlc) = query_htlc(deps, swap_hash) {
-            htlcs.push(htlc);
-        }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let items = match status {
+        Some(status) => ESCROWS
+            .idx
+            .maker_status
+            .prefix((maker_addr, status_key(&status)))
+            .range(deps.storage, start, None, Order::Ascending),
+        None => ESCROWS
+            .idx
+            .maker
+            .prefix(maker_addr)
+            .range(deps.storage, start, None, Order::Ascending),
+    };
+    items
+        .take(limit)
+        .map(|item| item.map(|(_, info)| to_htlc_info(info)))
+        .collect()
+}
+
+fn query_htlcs_by_resolver(
+    deps: Deps,
+    resolver: String,
+    status: Option<SwapStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EscrowInfoMsg>> {
+    let resolver_addr = deps.api.addr_validate(&resolver)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let items = match status {
+        Some(status) => ESCROWS
+            .idx
+            .resolver_status
+            .prefix((resolver_addr.to_string(), status_key(&status)))
+            .range(deps.storage, start, None, Order::Ascending),
+        None => ESCROWS
+            .idx
+            .resolver
+            .prefix(resolver_addr.to_string())
+            .range(deps.storage, start, None, Order::Ascending),
+    };
+    items
+        .take(limit)
+        .map(|item| item.map(|(_, info)| to_htlc_info(info)))
+        .collect()
+}
+
+fn query_htlcs_by_status(
+    deps: Deps,
+    status: SwapStatus,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EscrowInfoMsg>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    ESCROWS
+        .idx
+        .status
+        .prefix(status_key(&status))
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, info)| to_htlc_info(info)))
+        .collect()
+}
+
+fn query_htlcs_by_expiry(
+    deps: Deps,
+    expires_before: Option<u64>,
+    status: Option<SwapStatus>,
+    start_after: Option<ExpiryCursor>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EscrowInfoMsg>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let min = start_after.map(|c| Bound::exclusive((c.expiry, c.swap_hash)));
+    let max = expires_before.map(|before| Bound::exclusive((before, String::new())));
+    let items = match status {
+        Some(status) => ESCROWS
+            .idx
+            .status_expiry
+            .sub_prefix(status_key(&status))
+            .range(deps.storage, min, max, Order::Ascending),
+        None => ESCROWS.idx.expiry.range(deps.storage, min, max, Order::Ascending),
+    };
+    items
+        .take(limit)
+        .map(|item| item.map(|(_, info)| to_htlc_info(info)))
+        .collect()
+}
+
+fn query_predict_escrow_address(
+    deps: Deps,
+    env: Env,
+    immutables: EscrowImmutables,
+) -> StdResult<EscrowAddressResponse> {
+    // Addresses are hashed as validated, like `create_htlc` does
+    deps.api.addr_validate(&immutables.maker)?;
+    if let Some(resolver) = &immutables.resolver {
+        deps.api.addr_validate(resolver)?;
     }
-    
-    Ok(htlcs)
+    let salt = escrow_salt(&immutables)?;
+    Ok(EscrowAddressResponse {
+        address: predict_escrow_address(deps, &env, &salt)?,
+        salt,
+    })
 }
 
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
@@ -286,5 +799,48 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(ConfigResponse {
         admin: config.admin,
         htlc_code_id: config.htlc_code_id,
+        secret_hook: config.secret_hook,
     })
 }
+
+fn to_htlc_info(htlc_info: EscrowInfoState) -> EscrowInfoMsg {
+    EscrowInfoMsg {
+        swap_hash: htlc_info.swap_hash,
+        htlc_address: htlc_info.htlc_address,
+        mode: htlc_info.mode,
+        maker: htlc_info.maker,
+        resolver: htlc_info.resolver,
+        amount: htlc_info.amount,
+        asset: htlc_info.asset,
+        hashlock: htlc_info.hashlock,
+        hash_algorithm: htlc_info.hash_algorithm,
+        parts: htlc_info.parts,
+        timelocks: htlc_info.timelocks,
+        safety_deposit: htlc_info.safety_deposit,
+        status: htlc_info.status,
+        filled_amount: htlc_info.filled_amount,
+        created_at: htlc_info.created_at,
+        funded_at: htlc_info.funded_at,
+        completed_at: htlc_info.completed_at,
+        cancelled_at: htlc_info.cancelled_at,
+    }
+}
+
+// ================================================================================================
+// ADDRESS HELPERS
+// ================================================================================================
+
+/// Instantiate2 salt: sha256 over the JSON encoding of all the escrow's immutables.
+fn escrow_salt(immutables: &EscrowImmutables) -> StdResult<Binary> {
+    let hash = Sha256::digest(to_json_vec(immutables)?);
+    Ok(Binary::from(hash.to_vec()))
+}
+
+fn predict_escrow_address(deps: Deps, env: &Env, salt: &[u8]) -> StdResult<Addr> {
+    let config = CONFIG.load(deps.storage)?;
+    let code_info = deps.querier.query_wasm_code_info(config.htlc_code_id)?;
+    let creator = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let address = instantiate2_address(code_info.checksum.as_slice(), &creator, salt)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    deps.api.addr_humanize(&address)
+}
@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -15,6 +16,42 @@ pub enum ContractError {
     #[error("HTLC not found: {swap_hash}")]
     HTLCNotFound { swap_hash: String },
 
+    #[error("Invalid status transition for {swap_hash}: {from} -> {to}")]
+    InvalidStatusTransition { swap_hash: String, from: String, to: String },
+
     #[error("Invalid HTLC code ID")]
     InvalidHTLCCodeId,
+
+    #[error("Invalid timelocks")]
+    InvalidTimelocks,
+
+    #[error("Invalid amount")]
+    InvalidAmount,
+
+    #[error("Invalid denom")]
+    InvalidDenom,
+
+    #[error("Invalid asset: {msg}")]
+    InvalidAsset { msg: String },
+
+    #[error("Invalid number of parts")]
+    InvalidParts,
+
+    #[error("Invalid hashlock: expected {expected} bytes, got {got}")]
+    InvalidHashlock { expected: usize, got: usize },
+
+    #[error("Insufficient funds: required {required}, got {got}")]
+    InsufficientFunds { required: String, got: String },
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("HTLC for {swap_hash} instantiated at {got}, expected {expected}")]
+    UnexpectedHTLCAddress { swap_hash: String, expected: String, got: String },
 }
@@ -1,27 +1,123 @@
-use cosmwasm_std::{Addr, Uint128, Timestamp};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Coin, Uint128, Timestamp};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{Asset, EscrowMode, SwapStatus, HashAlgorithm, Timelocks};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub admin: Addr,
     pub htlc_code_id: u64,
+    /// Contract notified of revealed secrets
+    #[serde(default)]
+    pub secret_hook: Option<Addr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct EscrowInfo {
     pub swap_hash: String,
     pub htlc_address: Addr,
+    pub mode: EscrowMode,
     pub maker: Addr,
+    pub resolver: Option<Addr>,
     pub amount: Uint128,
-    pub denom: String,
+    pub asset: Asset,
     pub hashlock: Vec<u8>,
-    pub timelock: u64,
+    pub hash_algorithm: HashAlgorithm,
+    pub parts: Option<u32>,
+    pub timelocks: Timelocks,
+    pub safety_deposit: Option<Coin>,
+    pub status: SwapStatus,
+    /// Amount released so far, reported by the escrow
+    pub filled_amount: Uint128,
     pub created_at: Timestamp,
+    pub funded_at: Option<Timestamp>,
+    pub completed_at: Option<Timestamp>,
+    pub cancelled_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SecretInfo {
+    pub swap_hash: String,
+    pub index: u32,
+    pub secret: Vec<u8>,
+    pub revealed_at: Timestamp,
+}
+
+/// Secondary indexes over the escrow registry, all keyed back to swap_hash.
+pub struct EscrowIndexes<'a> {
+    pub maker: MultiIndex<'a, Addr, EscrowInfo, String>,
+    /// Escrows without a resolver yet are indexed under the empty string
+    pub resolver: MultiIndex<'a, String, EscrowInfo, String>,
+    pub status: MultiIndex<'a, String, EscrowInfo, String>,
+    /// Start of the cancellation stage
+    pub expiry: MultiIndex<'a, u64, EscrowInfo, String>,
+    // Status-narrowed variants of the indexes above, so filtered lists page on the index itself
+    pub maker_status: MultiIndex<'a, (Addr, String), EscrowInfo, String>,
+    pub resolver_status: MultiIndex<'a, (String, String), EscrowInfo, String>,
+    pub status_expiry: MultiIndex<'a, (String, u64), EscrowInfo, String>,
+}
+
+impl<'a> IndexList<EscrowInfo> for EscrowIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<EscrowInfo>> + '_> {
+        let v: Vec<&dyn Index<EscrowInfo>> = vec![
+            &self.maker,
+            &self.resolver,
+            &self.status,
+            &self.expiry,
+            &self.maker_status,
+            &self.resolver_status,
+            &self.status_expiry,
+        ];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn status_key(status: &SwapStatus) -> String {
+    match status {
+        SwapStatus::Pending => "pending",
+        SwapStatus::Funded => "funded",
+        SwapStatus::Completed => "completed",
+        SwapStatus::Cancelled => "cancelled",
+    }
+    .to_string()
+}
+
+fn resolver_key(escrow: &EscrowInfo) -> String {
+    escrow.resolver.as_ref().map(Addr::to_string).unwrap_or_default()
 }
 
 // Storage
 pub const CONFIG: Item<Config> = Item::new("config");
-pub const ESCROWS: Map<String, EscrowInfo> = Map::new("htlcs");
-pub const MAKER_ESCROWS: Map<Addr, Vec<String>> = Map::new("maker_htlcs");
+pub const ESCROWS: IndexedMap<String, EscrowInfo, EscrowIndexes> = IndexedMap::new(
+    "htlcs",
+    EscrowIndexes {
+        maker: MultiIndex::new(|_pk, e| e.maker.clone(), "htlcs", "htlcs__maker"),
+        resolver: MultiIndex::new(|_pk, e| resolver_key(e), "htlcs", "htlcs__resolver"),
+        status: MultiIndex::new(|_pk, e| status_key(&e.status), "htlcs", "htlcs__status"),
+        expiry: MultiIndex::new(|_pk, e| e.timelocks.cancellation, "htlcs", "htlcs__expiry"),
+        maker_status: MultiIndex::new(
+            |_pk, e| (e.maker.clone(), status_key(&e.status)),
+            "htlcs",
+            "htlcs__maker_status",
+        ),
+        resolver_status: MultiIndex::new(
+            |_pk, e| (resolver_key(e), status_key(&e.status)),
+            "htlcs",
+            "htlcs__resolver_status",
+        ),
+        status_expiry: MultiIndex::new(
+            |_pk, e| (status_key(&e.status), e.timelocks.cancellation),
+            "htlcs",
+            "htlcs__status_expiry",
+        ),
+    },
+);
+
+// Revealed secrets reported by escrows: secret hash -> secret
+pub const SECRETS: Map<&[u8], SecretInfo> = Map::new("secrets");
+
+// Escrow instantiations awaiting their reply: reply id -> swap_hash
+pub const PENDING_INSTANTIATIONS: Map<u64, String> = Map::new("pending_instantiations");
+pub const NEXT_REPLY_ID: Item<u64> = Item::new("next_reply_id");
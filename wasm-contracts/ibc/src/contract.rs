@@ -1,14 +1,38 @@
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Addr, Uint128, Timestamp, WasmMsg,
+    entry_point, to_json_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Reply,
+    Response, StdError, StdResult,
 };
+use cw_storage_plus::Bound;
+use htlc_msgs::factory::EscrowImmutables;
+use htlc_msgs::types::{Asset, EscrowMode, HashAlgorithm, SwapStatus};
+use htlc_msgs::FactoryContract;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, OrderInfo, OrderStatus, ConfigResponse};
-use crate::state::{Config, Order, CONFIG, ORDERS, MAKER_ORDERS};
-use crate::ibc::{process_ibc_packet, send_create_htlc_ibc};
 
-#[entry_point]
+// Pagination settings for list queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+use crate::ica;
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, QueryMsg, OrderInfo, OrderStatus, ConfigResponse, RouteInfo,
+    Actor, IcaStatus, InterchainAccountInfo, RelayPacket, RelayerSetResponse,
+};
+use crate::state::{
+    Config, InterchainAccount, Order, RelayerSet, Route, CONFIG, ORDERS, MAKER_ORDERS, ROUTES,
+    INTERCHAIN_ACCOUNTS, RELAYER_SET, RELAY_OUTBOX, FUNDING_ORDER, RECEIVING_ORDER,
+};
+use crate::relayer::validate_relayer_set;
+use crate::transport::{
+    acknowledge_relay_packet, receive_gmp, receive_hyperlane, take_packet_fee, validate_transport,
+};
+use crate::ibc::{
+    convert_address, process_ibc_packet, reply_create_htlc_failed, reply_fund_htlc_failed,
+    reply_secret_reveal_failed, reply_secret_revealed, send_create_htlc_ibc, send_secret_revealed_ibc,
+    send_update_status_ibc, OrderPacket, CREATE_HTLC_REPLY_ID, FUND_HTLC_REPLY_ID,
+    SECRET_REVEAL_REPLY_ID,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
@@ -18,7 +42,6 @@ pub fn instantiate(
     let config = Config {
         admin: deps.api.addr_validate(&msg.admin)?,
         htlc_factory: deps.api.addr_validate(&msg.htlc_factory)?,
-        ibc_channel: msg.ibc_channel,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -26,11 +49,10 @@ pub fn instantiate(
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("admin", config.admin)
-        .add_attribute("htlc_factory", config.htlc_factory)
-        .add_attribute("ibc_channel", config.ibc_channel))
+        .add_attribute("htlc_factory", config.htlc_factory))
 }
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
@@ -41,69 +63,195 @@ pub fn execute(
         ExecuteMsg::CreateOrder {
             swap_hash,
             maker,
+            resolver,
             amount,
             denom,
             hashlock,
-            timelock,
+            timelocks,
+            dst_timelocks,
             target_chain,
-        } => create_order(deps, env, info, swap_hash, maker, amount, denom, hashlock, timelock, target_chain),
+        } => create_order(deps, env, info, OrderPacket {
+            swap_hash,
+            maker,
+            amount,
+            denom,
+            hashlock,
+            timelocks,
+            dst_timelocks,
+        }, resolver, target_chain),
         
         ExecuteMsg::UpdateOrderStatus { swap_hash, status } => {
-            update_order_status(deps, info, swap_hash, status)
+            update_order_status(deps, env, info, swap_hash, status)
         }
         
-        ExecuteMsg::ProcessIBCPacket { channel_id, packet_data } => {
-            process_ibc_packet(deps, env, info, channel_id, packet_data)
+        ExecuteMsg::SecretRevealed { swap_hash, secret } => {
+            propagate_secret(deps, env, swap_hash, secret)
+        }
+
+        ExecuteMsg::StatusChanged { swap_hash, status } => {
+            status_changed(deps, env, info, swap_hash, status)
+        }
+
+        ExecuteMsg::RefundOrder { swap_hash } => refund_order(deps, swap_hash),
+
+        ExecuteMsg::ProcessIBCPacket { channel_id, packet_data, attestations } => {
+            process_ibc_packet(deps, env, channel_id, packet_data, attestations)
+        }
+
+        ExecuteMsg::AcknowledgeRelayPacket { channel_id, sequence, attestations } => {
+            acknowledge_relay_packet(deps, env, channel_id, sequence, attestations)
         }
         
         ExecuteMsg::UpdateHTLCFactory { htlc_factory } => {
             update_htlc_factory(deps, info, htlc_factory)
         }
         
-        ExecuteMsg::UpdateIBCChannel { channel_id } => {
-            update_ibc_channel(deps, info, channel_id)
+        ExecuteMsg::SetRoute {
+            chain_id,
+            channel_id,
+            transport,
+            remote_contract,
+            remote_factory,
+            address_prefix,
+            timeout_seconds,
+        } => set_route(deps, info, Route {
+            chain_id,
+            channel_id,
+            transport,
+            remote_contract,
+            remote_factory,
+            address_prefix,
+            timeout_seconds,
+        }),
+
+        ExecuteMsg::RemoveRoute { chain_id } => remove_route(deps, info, chain_id),
+
+        ExecuteMsg::RegisterInterchainAccount {
+            chain_id,
+            connection_id,
+            counterparty_connection_id,
+            remote_factory,
+            timeout_seconds,
+        } => register_interchain_account(deps, env, info, InterchainAccount {
+            chain_id,
+            connection_id,
+            counterparty_connection_id,
+            remote_factory,
+            timeout_seconds,
+            status: IcaStatus::Opening,
+            channel_id: None,
+            address: None,
+        }),
+
+        ExecuteMsg::FundRemoteHTLC { swap_hash, htlc_address } => {
+            fund_remote_htlc(deps, env, info, swap_hash, htlc_address)
         }
+
+        ExecuteMsg::CancelRemoteHTLC { swap_hash } => cancel_remote_htlc(deps, env, info, swap_hash),
         
+        ExecuteMsg::ReceiveGmp { source_chain, source_address, payload } => {
+            receive_gmp(deps, env, info, source_chain, source_address, payload)
+        }
+
+        ExecuteMsg::Handle { origin, sender, body } => {
+            receive_hyperlane(deps, env, info, origin, sender, body)
+        }
+
+        ExecuteMsg::UpdateRelayerSet { relayers, threshold } => {
+            update_relayer_set(deps, info, RelayerSet { relayers, threshold })
+        }
+
         ExecuteMsg::UpdateAdmin { admin } => {
             update_admin(deps, info, admin)
         }
     }
 }
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::GetOrder { swap_hash } => to_json_binary(&query_order(deps, swap_hash)?),
-        QueryMsg::GetOrdersByMaker { maker } => to_json_binary(&query_orders_by_maker(deps, maker)?),
+        QueryMsg::GetOrder { swap_hash } => to_json_binary(&query_order(deps, &env, swap_hash)?),
+        QueryMsg::GetOrdersByMaker { maker } => {
+            to_json_binary(&query_orders_by_maker(deps, &env, maker)?)
+        }
+        QueryMsg::GetRoute { chain_id } => to_json_binary(&query_route(deps, chain_id)?),
+        QueryMsg::ListRoutes { start_after, limit } => {
+            to_json_binary(&query_routes(deps, start_after, limit)?)
+        }
+        QueryMsg::GetInterchainAccount { chain_id } => {
+            to_json_binary(&query_interchain_account(deps, chain_id)?)
+        }
+        QueryMsg::GetRelayerSet {} => to_json_binary(&query_relayer_set(deps)?),
+        QueryMsg::ListRelayPackets { channel_id, start_after, limit } => {
+            to_json_binary(&query_relay_packets(deps, channel_id, start_after, limit)?)
+        }
         QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
     }
 }
 
+// ================================================================================================
+// REPLY HANDLER
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // Submessages that park their order in a transient item reply either way to clear it
+        CREATE_HTLC_REPLY_ID => match msg.result.into_result() {
+            Ok(_) => {
+                RECEIVING_ORDER.remove(deps.storage);
+                Ok(Response::new())
+            }
+            Err(error) => reply_create_htlc_failed(deps, error),
+        },
+        SECRET_REVEAL_REPLY_ID => match msg.result.into_result() {
+            Ok(_) => reply_secret_revealed(deps, &env),
+            Err(error) => reply_secret_reveal_failed(deps, error),
+        },
+        FUND_HTLC_REPLY_ID => match msg.result.into_result() {
+            Ok(_) => {
+                FUNDING_ORDER.remove(deps.storage);
+                Ok(Response::new())
+            }
+            Err(error) => reply_fund_htlc_failed(deps, &env, error),
+        },
+        id => Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id: {}", id)))),
+    }
+}
+
 // ================================================================================================
 // EXECUTE FUNCTIONS
 // ================================================================================================
 
 fn create_order(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
-    swap_hash: String,
-    maker: String,
-    amount: Uint128,
-    denom: String,
-    hashlock: Vec<u8>,
-    timelock: u64,
+    info: MessageInfo,
+    packet: OrderPacket,
+    resolver: String,
     target_chain: String,
 ) -> Result<Response, ContractError> {
+    let OrderPacket { swap_hash, maker, amount, denom, hashlock, timelocks, .. } = packet.clone();
+
+    // Resolve the destination chain: a route to its order contract, or an interchain account
+    let route = ROUTES.may_load(deps.storage, target_chain.clone())?;
+    let account = match route {
+        Some(_) => None,
+        None => Some(ica::open_account(deps.storage, &target_chain)?),
+    };
+
     // Check if order already exists
     if ORDERS.has(deps.storage, swap_hash.clone()) {
         return Err(ContractError::OrderAlreadyExists { order_id: swap_hash });
     }
 
-    // Validate timelock
-    if timelock <= env.block.time.seconds() {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Timelock expired")));
-    }
+    // Validate timelocks of both escrows against the latency of the way to the target chain
+    let latency = match (&route, &account) {
+        (Some(route), _) => route.timeout_seconds,
+        (None, Some(account)) => account.timeout_seconds,
+        (None, None) => return Err(ContractError::UnknownChain { chain_id: target_chain }),
+    };
+    packet.validate_timelocks(env.block.time.seconds(), latency)?;
 
     // Validate amount
     if amount.is_zero() {
@@ -116,18 +264,71 @@ fn create_order(
     }
 
     let maker_addr = deps.api.addr_validate(&maker)?;
+    // The interchain account funds the remote HTLC of its orders, so the source HTLC pays this
+    // contract, the account's owner, instead of a resolver of the caller's choosing
+    let resolver_addr = match account {
+        Some(_) => env.contract.address.clone(),
+        None => deps.api.addr_validate(&resolver)?,
+    };
 
-    let order = Order {
+    // Orders commit the maker to a swap, so only the maker creates them
+    if info.sender != maker_addr {
+        return Err(ContractError::Unauthorized {
+            msg: "Only the maker can create an order".to_string(),
+        });
+    }
+
+    // The maker pays the transport fee of the order packet. Further funds must cover the
+    // order exactly and are held until the counterparty confirms
+    let locked_funds = match &route {
+        Some(route) => take_packet_fee(route, &info.funds)?,
+        None => info.funds.clone(),
+    };
+    match locked_funds.as_slice() {
+        [] => {}
+        [coin] if coin.denom == denom && coin.amount == amount => {}
+        _ => {
+            return Err(ContractError::Std(StdError::generic_err(
+                "Attached funds must equal the order amount",
+            )))
+        }
+    }
+
+    let mut order = Order {
         swap_hash: swap_hash.clone(),
         maker: maker_addr.clone(),
         amount,
         denom: denom.clone(),
         hashlock,
-        timelock,
+        timelocks: timelocks.clone(),
         target_chain: target_chain.clone(),
         status: OrderStatus::Pending,
         created_at: env.block.time,
         htlc_address: None,
+        remote_htlc_address: None,
+        locked_funds,
+        failure_reason: None,
+        secret: None,
+        ica_txs: vec![],
+        last_sequence: 0,
+        transitions: vec![],
+    };
+
+    // Ask the target chain to create its HTLC, addressing the maker with that chain's prefix
+    let remote_msg = match (route, account) {
+        (Some(route), _) => {
+            let remote_order = OrderPacket {
+                maker: convert_address(&packet.maker, &route.address_prefix)?,
+                ..packet
+            };
+            send_create_htlc_ibc(&mut deps, &env, &route, remote_order)?
+        }
+        (None, Some(account)) => {
+            let (msg, tx) = ica::create_remote_htlc(&env, &account, packet)?;
+            order.ica_txs.push(tx);
+            Some(msg.into())
+        }
+        (None, None) => return Err(ContractError::UnknownChain { chain_id: target_chain }),
     };
 
     ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
@@ -137,45 +338,36 @@ fn create_order(
         .may_load(deps.storage, maker_addr.clone())?
         .unwrap_or_default();
     maker_orders.push(swap_hash.clone());
-    MAKER_ORDERS.save(deps.storage, maker_addr, &maker_orders)?;
-
-    // Create HTLC via factory
-    let create_htlc_msg = WasmMsg::Execute {
-        contract_addr: CONFIG.load(deps.storage)?.htlc_factory.to_string(),
-        msg: cosmwasm_std::to_json_binary(&serde_json::json!({
-            "create_h_t_l_c": {
-                "swap_hash": swap_hash.clone(),
-                "maker": maker.clone(),
-                "amount": amount.to_string(),
-                "denom": denom,
-                "hashlock": order.hashlock.clone(),
-                "timelock": timelock
-            }
-        }))?,
-        funds: vec![],
-    };
-
-    // Send IBC packet to target chain
-    let _ibc_response = send_create_htlc_ibc(
-        deps,
-        env,
-        swap_hash.clone(),
-        maker,
-        amount,
-        order.denom.clone(),
-        order.hashlock.clone(),
-        timelock,
-        target_chain,
+    MAKER_ORDERS.save(deps.storage, maker_addr.clone(), &maker_orders)?;
+
+    // Create the maker's source HTLC via factory, for the resolver to withdraw
+    let create_htlc_msg = FactoryContract(CONFIG.load(deps.storage)?.htlc_factory).create_escrow(
+        EscrowImmutables {
+            swap_hash: swap_hash.clone(),
+            mode: EscrowMode::Source,
+            maker: maker_addr.to_string(),
+            resolver: Some(resolver_addr.to_string()),
+            amount,
+            asset: Asset::Native { denom },
+            hashlock: order.hashlock.clone(),
+            hash_algorithm: HashAlgorithm::default(),
+            parts: None,
+            timelocks: timelocks.clone(),
+            safety_deposit: None,
+        },
     )?;
 
     Ok(Response::new()
         .add_message(create_htlc_msg)
+        .add_messages(remote_msg)
         .add_attribute("method", "create_order")
-        .add_attribute("swap_hash", swap_hash))
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("target_chain", target_chain))
 }
 
 fn update_order_status(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     swap_hash: String,
     status: OrderStatus,
@@ -183,21 +375,166 @@ fn update_order_status(
     let mut order = ORDERS.load(deps.storage, swap_hash.clone())
         .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
 
-    // Only admin or HTLC factory can update status
+    // The sender's role decides which transitions it may make
     let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.admin && info.sender != config.htlc_factory {
-        return Err(ContractError::Unauthorized { 
-            msg: "Only admin or HTLC factory can update order status".to_string() 
+    let actor = order_actor(deps.as_ref(), &config, &order, &info.sender)?;
+    order.transition(status.clone(), actor, env.block.time)?;
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    // Mirror the change on the counterparty order contract; interchain account hosts have none
+    let mut messages = vec![];
+    if let Some(route) = ROUTES.may_load(deps.storage, order.target_chain.clone())? {
+        // The sender pays the transport fee of the status packet
+        if !take_packet_fee(&route, &info.funds)?.is_empty() {
+            return Err(ContractError::Std(StdError::generic_err(
+                "Only the transport fee may be attached",
+            )));
+        }
+        messages.extend(send_update_status_ibc(&mut deps, &env, &route, swap_hash.clone(), status.clone())?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "update_order_status")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("status", format!("{:?}", status)))
+}
+
+/// Role `sender` plays for `order`; nobody else may change its status.
+fn order_actor(deps: Deps, config: &Config, order: &Order, sender: &Addr) -> Result<Actor, ContractError> {
+    if *sender == config.htlc_factory {
+        return Ok(Actor::Factory);
+    }
+    if *sender == order.maker {
+        return Ok(Actor::Maker);
+    }
+
+    // The resolver is whoever funded the local HTLC
+    let resolver = FactoryContract(config.htlc_factory.clone())
+        .htlc(&deps.querier, order.swap_hash.clone())
+        .ok()
+        .and_then(|htlc| htlc.resolver);
+    if resolver.as_ref() == Some(sender) {
+        return Ok(Actor::Resolver);
+    }
+
+    Err(ContractError::Unauthorized {
+        msg: "Only the maker, resolver or HTLC factory can update order status".to_string(),
+    })
+}
+
+fn propagate_secret(
+    mut deps: DepsMut,
+    env: Env,
+    swap_hash: String,
+    secret: Vec<u8>,
+) -> Result<Response, ContractError> {
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+
+    // Verify secret with the hash algorithm of the order's local HTLC
+    let htlc = FactoryContract(CONFIG.load(deps.storage)?.htlc_factory)
+        .htlc(&deps.querier, swap_hash.clone())?;
+    if htlc.hash_algorithm.digest(&secret) != order.hashlock {
+        return Err(ContractError::InvalidSecret { order_id: swap_hash });
+    }
+
+    // A secret learned from the counterparty isn't sent back to it
+    if order.secret.is_some() {
+        return Ok(Response::new()
+            .add_attribute("method", "propagate_secret")
+            .add_attribute("swap_hash", swap_hash)
+            .add_attribute("forwarded", "false"));
+    }
+    // A known secret alone pays nobody: the order settles once its local HTLC has
+    order.secret = Some(secret.clone());
+    if htlc.status == SwapStatus::Completed {
+        order.complete(env.block.time)?;
+    }
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    // Interchain account hosts run no order contract to receive it
+    let route = ROUTES.may_load(deps.storage, order.target_chain.clone())?;
+    let forwarded = route.is_some();
+    let mut messages = vec![];
+    if let Some(route) = route {
+        messages.extend(send_secret_revealed_ibc(&mut deps, &env, &route, swap_hash.clone(), secret)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "propagate_secret")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("forwarded", forwarded.to_string())
+        .add_messages(messages))
+}
+
+/// Escrow status reported by the factory's hook: the factory moves the order along with its
+/// local HTLC. The counterparty follows its own HTLC, so nothing is mirrored.
+fn status_changed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_hash: String,
+    status: SwapStatus,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.htlc_factory {
+        return Err(ContractError::Unauthorized {
+            msg: "Only the HTLC factory reports escrow status".to_string(),
         });
     }
 
-    order.status = status.clone();
+    // The factory reports every escrow, including those of no order
+    let Some(mut order) = ORDERS.may_load(deps.storage, swap_hash.clone())? else {
+        return Ok(Response::new()
+            .add_attribute("method", "status_changed")
+            .add_attribute("swap_hash", swap_hash)
+            .add_attribute("applied", "false"));
+    };
+
+    // A verified secret may have settled the order already
+    let to = match status {
+        SwapStatus::Pending => None,
+        SwapStatus::Funded => Some(OrderStatus::Funded),
+        SwapStatus::Completed => Some(OrderStatus::Completed),
+        SwapStatus::Cancelled => Some(OrderStatus::Cancelled),
+    };
+    let to = to.filter(|to| *to != order.status);
+    if let Some(to) = &to {
+        order.transition(to.clone(), Actor::Factory, env.block.time)?;
+        ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "status_changed")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("applied", to.is_some().to_string())
+        .add_attribute("status", format!("{:?}", order.status)))
+}
+
+fn refund_order(deps: DepsMut, swap_hash: String) -> Result<Response, ContractError> {
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+
+    // Only orders the counterparty never created can be refunded here
+    if order.status != OrderStatus::Failed {
+        return Err(ContractError::InvalidOrderStatus);
+    }
+    if order.locked_funds.is_empty() {
+        return Err(ContractError::NothingToRefund { order_id: swap_hash });
+    }
+
+    let refund = BankMsg::Send {
+        to_address: order.maker.to_string(),
+        amount: std::mem::take(&mut order.locked_funds),
+    };
     ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
 
     Ok(Response::new()
-        .add_attribute("method", "update_order_status")
+        .add_message(refund)
+        .add_attribute("method", "refund_order")
         .add_attribute("swap_hash", swap_hash)
-        .add_attribute("status", format!("{:?}", status)))
+        .add_attribute("maker", order.maker))
 }
 
 fn update_htlc_factory(
@@ -224,26 +561,219 @@ fn update_htlc_factory(
         .add_attribute("htlc_factory", new_htlc_factory))
 }
 
-fn update_ibc_channel(
+fn set_route(deps: DepsMut, info: MessageInfo, route: Route) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Check if sender is admin
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can manage routes".to_string()
+        });
+    }
+
+    // Validate route
+    if route.chain_id.is_empty() || route.channel_id.is_empty() || route.remote_factory.is_empty() {
+        return Err(ContractError::InvalidRoute {
+            msg: "chain_id, channel_id and remote_factory are required".to_string(),
+        });
+    }
+    if route.remote_contract.is_empty() {
+        return Err(ContractError::InvalidRoute { msg: "Empty remote contract".to_string() });
+    }
+    validate_transport(deps.as_ref(), &route)?;
+    if route.address_prefix.is_empty() {
+        return Err(ContractError::InvalidRoute { msg: "Empty address prefix".to_string() });
+    }
+    if route.timeout_seconds == 0 {
+        return Err(ContractError::InvalidRoute { msg: "Timeout must be positive".to_string() });
+    }
+
+    if INTERCHAIN_ACCOUNTS.has(deps.storage, route.chain_id.clone()) {
+        return Err(ContractError::InvalidRoute {
+            msg: format!("{} is served by an interchain account", route.chain_id),
+        });
+    }
+
+    // The channel index is unique, so a channel can't be routed to two chains
+    ROUTES.save(deps.storage, route.chain_id.clone(), &route)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_route")
+        .add_attribute("chain_id", route.chain_id)
+        .add_attribute("channel_id", route.channel_id)
+        .add_attribute("timeout_seconds", route.timeout_seconds.to_string()))
+}
+
+fn remove_route(deps: DepsMut, info: MessageInfo, chain_id: String) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Check if sender is admin
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can manage routes".to_string()
+        });
+    }
+
+    if !ROUTES.has(deps.storage, chain_id.clone()) {
+        return Err(ContractError::UnknownChain { chain_id });
+    }
+    ROUTES.remove(deps.storage, chain_id.clone())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "remove_route")
+        .add_attribute("chain_id", chain_id))
+}
+
+fn register_interchain_account(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    channel_id: String,
+    account: InterchainAccount,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    
+    let config = CONFIG.load(deps.storage)?;
+
     // Check if sender is admin
     if info.sender != config.admin {
-        return Err(ContractError::Unauthorized { 
-            msg: "Only admin can update IBC channel".to_string() 
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can manage routes".to_string()
         });
     }
 
-    config.ibc_channel = channel_id.clone();
-    CONFIG.save(deps.storage, &config)?;
+    // Validate account
+    if account.chain_id.is_empty() || account.remote_factory.is_empty() {
+        return Err(ContractError::InvalidRoute {
+            msg: "chain_id and remote_factory are required".to_string(),
+        });
+    }
+    if account.connection_id.is_empty() || account.counterparty_connection_id.is_empty() {
+        return Err(ContractError::InvalidRoute { msg: "Empty connection id".to_string() });
+    }
+    if account.timeout_seconds == 0 {
+        return Err(ContractError::InvalidRoute { msg: "Timeout must be positive".to_string() });
+    }
+    if ROUTES.has(deps.storage, account.chain_id.clone()) {
+        return Err(ContractError::InvalidRoute {
+            msg: format!("{} is already routed to an order contract", account.chain_id),
+        });
+    }
+
+    // A closed account keeps its address on the host and is reopened by registering again
+    let previous = INTERCHAIN_ACCOUNTS.may_load(deps.storage, account.chain_id.clone())?;
+    if previous.as_ref().map(|a| &a.status) == Some(&IcaStatus::Open) {
+        return Err(ContractError::InvalidRoute {
+            msg: format!("Interchain account on {} is already open", account.chain_id),
+        });
+    }
+    let account = InterchainAccount {
+        address: previous.and_then(|a| a.address),
+        ..account
+    };
+
+    let register_msg = ica::register(&env, &account)?;
+    INTERCHAIN_ACCOUNTS.save(deps.storage, account.chain_id.clone(), &account)?;
 
     Ok(Response::new()
-        .add_attribute("method", "update_ibc_channel")
-        .add_attribute("channel_id", channel_id))
+        .add_message(register_msg)
+        .add_attribute("method", "register_interchain_account")
+        .add_attribute("chain_id", account.chain_id)
+        .add_attribute("connection_id", account.connection_id))
+}
+
+fn fund_remote_htlc(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_hash: String,
+    htlc_address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // The interchain account's balance is shared by all orders, so only the admin spends it
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can fund remote HTLCs".to_string()
+        });
+    }
+
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+    if order.status != OrderStatus::Created {
+        return Err(ContractError::InvalidOrderStatus);
+    }
+
+    // Only orders whose source HTLC pays this contract may spend the account's balance
+    let htlc = FactoryContract(config.htlc_factory).htlc(&deps.querier, swap_hash.clone())?;
+    if htlc.resolver.as_ref() != Some(&env.contract.address) {
+        return Err(ContractError::Unauthorized {
+            msg: "Only orders resolved by this contract can be funded from its interchain account".to_string(),
+        });
+    }
+
+    let account = ica::open_account(deps.storage, &order.target_chain)?;
+    let (ibc_msg, tx) = ica::fund_remote_htlc(&env, &account, &order, &htlc_address)?;
+    order.remote_htlc_address = Some(htlc_address.clone());
+    order.ica_txs.push(tx);
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_message(ibc_msg)
+        .add_attribute("method", "fund_remote_htlc")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("htlc_address", htlc_address))
+}
+
+fn cancel_remote_htlc(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    swap_hash: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+
+    // Only admin or the order's maker can cancel
+    if info.sender != config.admin && info.sender != order.maker {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin or maker can cancel remote HTLCs".to_string()
+        });
+    }
+
+    let htlc_address = order.remote_htlc_address.clone().ok_or(ContractError::HTLCContractNotFound)?;
+    let account = ica::open_account(deps.storage, &order.target_chain)?;
+    let (ibc_msg, tx) = ica::cancel_remote_htlc(&env, &account, &order, &htlc_address)?;
+    order.ica_txs.push(tx);
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_message(ibc_msg)
+        .add_attribute("method", "cancel_remote_htlc")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("htlc_address", htlc_address))
+}
+
+fn update_relayer_set(
+    deps: DepsMut,
+    info: MessageInfo,
+    set: RelayerSet,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Check if sender is admin
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {
+            msg: "Only admin can update the relayer set".to_string()
+        });
+    }
+
+    // Replacing the whole set rotates keys atomically
+    validate_relayer_set(&set)?;
+    RELAYER_SET.save(deps.storage, &set)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_relayer_set")
+        .add_attribute("relayers", set.relayers.len().to_string())
+        .add_attribute("threshold", set.threshold.to_string()))
 }
 
 fn update_admin(
@@ -274,29 +804,36 @@ fn update_admin(
 // QUERY FUNCTIONS
 // ================================================================================================
 
-fn query_order(deps: Deps, swap_hash: String) -> StdResult<OrderInfo> {
+fn query_order(deps: Deps, env: &Env, swap_hash: String) -> StdResult<OrderInfo> {
     let order = ORDERS.load(deps.storage, swap_hash)?;
+    let status = order.status_at(env.block.time);
     Ok(OrderInfo {
         swap_hash: order.swap_hash,
         maker: order.maker,
         amount: order.amount,
         denom: order.denom,
         hashlock: order.hashlock,
-        timelock: order.timelock,
+        timelocks: order.timelocks,
         target_chain: order.target_chain,
         htlc_address: order.htlc_address,
-        status: order.status,
+        remote_htlc_address: order.remote_htlc_address,
+        locked_funds: order.locked_funds,
+        status,
+        failure_reason: order.failure_reason,
+        secret: order.secret,
+        ica_txs: order.ica_txs,
+        transitions: order.transitions,
         created_at: order.created_at,
     })
 }
 
-fn query_orders_by_maker(deps: Deps, maker: String) -> StdResult<Vec<OrderInfo>> {
+fn query_orders_by_maker(deps: Deps, env: &Env, maker: String) -> StdResult<Vec<OrderInfo>> {
     let maker_addr = deps.api.addr_validate(&maker)?;
     let swap_hashes = MAKER_ORDERS.load(deps.storage, maker_addr).unwrap_or_default();
     
     let mut orders = Vec::new();
     for swap_hash in swap_hashes {
-        if let Ok(order) = query_order(deps, swap_hash) {
+        if let Ok(order) = query_order(deps, env, swap_hash) {
             orders.push(order);
         }
     }
@@ -309,6 +846,74 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(ConfigResponse {
         admin: config.admin,
         htlc_factory: config.htlc_factory,
-        ibc_channel: config.ibc_channel,
     })
-}
\ No newline at end of file
+}
+
+fn query_route(deps: Deps, chain_id: String) -> StdResult<RouteInfo> {
+    let route = ROUTES.load(deps.storage, chain_id)?;
+    Ok(to_route_info(route))
+}
+
+fn query_routes(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<RouteInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    ROUTES
+        .range(deps.storage, start_after.map(Bound::exclusive), None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, route)| to_route_info(route)))
+        .collect()
+}
+
+fn query_relay_packets(
+    deps: Deps,
+    channel_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<RelayPacket>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    RELAY_OUTBOX
+        .prefix(channel_id)
+        .range(deps.storage, start_after.map(Bound::exclusive), None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(sequence, data)| RelayPacket { sequence, data }))
+        .collect()
+}
+
+fn to_route_info(route: Route) -> RouteInfo {
+    RouteInfo {
+        chain_id: route.chain_id,
+        channel_id: route.channel_id,
+        transport: route.transport,
+        remote_contract: route.remote_contract,
+        remote_factory: route.remote_factory,
+        address_prefix: route.address_prefix,
+        timeout_seconds: route.timeout_seconds,
+    }
+}
+fn query_interchain_account(deps: Deps, chain_id: String) -> StdResult<InterchainAccountInfo> {
+    let account = INTERCHAIN_ACCOUNTS.load(deps.storage, chain_id)?;
+    Ok(InterchainAccountInfo {
+        chain_id: account.chain_id,
+        connection_id: account.connection_id,
+        counterparty_connection_id: account.counterparty_connection_id,
+        remote_factory: account.remote_factory,
+        timeout_seconds: account.timeout_seconds,
+        status: account.status,
+        channel_id: account.channel_id,
+        address: account.address,
+    })
+}
+
+fn query_relayer_set(deps: Deps) -> StdResult<RelayerSetResponse> {
+    let set = RELAYER_SET.may_load(deps.storage)?.unwrap_or(RelayerSet {
+        relayers: vec![],
+        threshold: 0,
+    });
+    Ok(RelayerSetResponse {
+        relayers: set.relayers,
+        threshold: set.threshold,
+    })
+}
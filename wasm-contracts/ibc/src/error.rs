@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -12,9 +13,52 @@ pub enum ContractError {
     #[error("Invalid IBC channel: {channel}")]
     InvalidIBCChannel { channel: String },
 
+    #[error("Only unordered IBC channels are supported")]
+    InvalidIBCOrdering,
+
+    #[error("Invalid IBC channel version: {version}")]
+    InvalidIBCVersion { version: String },
+
+    #[error("Unknown chain: {chain_id}")]
+    UnknownChain { chain_id: String },
+
+    #[error("Invalid route: {msg}")]
+    InvalidRoute { msg: String },
+
+    #[error("Invalid interchain account channel: {msg}")]
+    InvalidIcaChannel { msg: String },
+
+    #[error("Interchain account on {chain_id} is not open")]
+    InterchainAccountNotReady { chain_id: String },
+
+    #[error("Invalid address {address}: {msg}")]
+    InvalidAddress { address: String, msg: String },
+
     #[error("Invalid packet data")]
     InvalidPacketData,
 
+    #[error("Invalid relayer set: {msg}")]
+    InvalidRelayerSet { msg: String },
+
+    #[error("No relayer set is configured")]
+    NoRelayerSet,
+
+    #[error("Insufficient attestations: {valid} valid of {threshold} required")]
+    InsufficientAttestations { valid: u32, threshold: u32 },
+
+    #[error("Invalid packet source: {msg}")]
+    InvalidPacketSource { msg: String },
+
+    #[error("Packet {sequence} on {channel} was already processed")]
+    ReplayedPacket { channel: String, sequence: u64 },
+
+    #[error("Relay packet {sequence} on {channel} not found")]
+    RelayPacketNotFound { channel: String, sequence: u64 },
+
+    #[error("Stale packet {sequence} for order {order_id}")]
+    StalePacket { order_id: String, sequence: u64 },
+
+
     #[error("HTLC contract not found")]
     HTLCContractNotFound,
 
@@ -24,9 +68,33 @@ pub enum ContractError {
     #[error("Order already exists: {order_id}")]
     OrderAlreadyExists { order_id: String },
 
+    #[error("Invalid denom: {denom}")]
+    InvalidDenom { denom: String },
+
+    #[error("Timelock expired at {cancellation}")]
+    TimelockExpired { cancellation: u64 },
+
+    #[error("Invalid timelocks: {msg}")]
+    InvalidTimelocks { msg: String },
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Attach the transport fee of {fee}")]
+    InsufficientFee { fee: String },
+
+    #[error("Nothing to refund for order {order_id}")]
+    NothingToRefund { order_id: String },
+
+    #[error("Secret does not match the hashlock of order {order_id}")]
+    InvalidSecret { order_id: String },
+
     #[error("Invalid order status")]
     InvalidOrderStatus,
 
+    #[error("{actor} cannot move an order from {from} to {to}")]
+    InvalidStatusTransition { from: String, to: String, actor: String },
+
     #[error("IBC packet processing failed")]
     IBCPacketProcessingFailed,
 
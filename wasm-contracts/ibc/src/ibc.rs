@@ -1,195 +1,851 @@
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, WasmMsg, IbcMsg, to_json_binary};
+use std::fmt;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    entry_point, from_json, to_json_binary, BankMsg, Binary, Coin, CosmosMsg, DepsMut, Env,
+    Ibc3ChannelOpenResponse, IbcBasicResponse, IbcChannel, IbcChannelCloseMsg,
+    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcOrder, IbcPacket,
+    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
+    Never, Response, StdResult, SubMsg, Uint128,
+};
+use htlc_msgs::escrow::ExecuteMsg as EscrowExecuteMsg;
+use htlc_msgs::factory::EscrowImmutables;
+use htlc_msgs::types::{Asset, EscrowMode, HashAlgorithm, SwapStatus};
+use htlc_msgs::{EscrowContract, FactoryContract};
+
 use crate::error::ContractError;
-use crate::msg::{OrderInfo, OrderStatus};
-use crate::state::{Config, Order, CONFIG, ORDERS};
+use crate::ica;
+use crate::msg::{Actor, Attestation, OrderStatus, Timelocks, Transport};
+use crate::relayer::{attestation_digest, verify_attestations};
+use crate::transport;
+use crate::state::{
+    ChannelInfo, Order, Route, CHANNELS, CONFIG, ICA_CHANNELS, ORDERS, RECEIVED_SEQUENCES,
+    FUNDING_ORDER, RECEIVING_ORDER, RELAYER_SET, REVEALING_ORDER, ROUTES, SEND_SEQUENCES,
+};
+
+/// Reply id for the factory call creating an incoming order's HTLC.
+pub const CREATE_HTLC_REPLY_ID: u64 = 1;
+
+/// Reply id for revealing a secret received from the counterparty on the local HTLC.
+pub const SECRET_REVEAL_REPLY_ID: u64 = 2;
+
+/// Reply id for locking held maker funds in the local HTLC once the counterparty confirmed.
+pub const FUND_HTLC_REPLY_ID: u64 = 3;
+
+/// Channel version both ends must agree on.
+pub const IBC_VERSION: &str = "htlc-orders-1";
+
+/// Orders are keyed by swap hash, so packets don't need to arrive in sequence.
+pub const IBC_ORDERING: IbcOrder = IbcOrder::Unordered;
+
+// ================================================================================================
+// PACKET TYPES
+// ================================================================================================
+
+/// Envelope binding an action to the order contract that sent it.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct IBCPacket {
+    pub source_chain: String,
+    pub source_contract: String,
+    /// Per-channel counter assigned by the sender, starting at 1
+    pub sequence: u64,
+    pub action: IBCAction,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum IBCAction {
+    CreateHTLC(OrderPacket),
+    UpdateOrderStatus {
+        swap_hash: String,
+        status: OrderStatus,
+    },
+    SecretRevealed {
+        swap_hash: String,
+        secret: Vec<u8>,
+    },
+}
+
+/// Order parameters carried to the counterparty chain.
+#[cw_serde]
+pub struct OrderPacket {
+    pub swap_hash: String,
+    pub maker: String,
+    pub amount: Uint128,
+    pub denom: String,
+    pub hashlock: Vec<u8>,
+    /// Timelocks of the maker's source escrow on the sending chain
+    pub timelocks: Timelocks,
+    /// Timelocks of the destination escrow on the receiving chain
+    pub dst_timelocks: Timelocks,
+}
+
+impl OrderPacket {
+    /// Both schedules must be ordered and unexpired, and the destination escrow must open no
+    /// later and become cancellable earlier than the source escrow, so a secret revealed on the
+    /// destination chain leaves time to withdraw on the source chain. `latency` is the packet
+    /// timeout of the route between the chains: the destination escrow must outlive a packet in
+    /// flight, and the source escrow must outlive the destination one by another.
+    pub fn validate_timelocks(&self, now: u64, latency: u64) -> Result<(), ContractError> {
+        let (src, dst) = (&self.timelocks, &self.dst_timelocks);
+        if !src.is_valid(now) || !dst.is_valid(now) {
+            return Err(ContractError::InvalidTimelocks {
+                msg: "timelocks must be ordered and not expired".to_string(),
+            });
+        }
+        if dst.cancellation >= src.cancellation || dst.withdrawal > src.withdrawal {
+            return Err(ContractError::InvalidTimelocks {
+                msg: "destination escrow must unlock no later and be cancellable before the source escrow".to_string(),
+            });
+        }
+        if dst.cancellation < now.saturating_add(latency) {
+            return Err(ContractError::InvalidTimelocks {
+                msg: format!("destination escrow must stay open for the {latency}s packet timeout"),
+            });
+        }
+        if src.cancellation - dst.cancellation < latency {
+            return Err(ContractError::InvalidTimelocks {
+                msg: format!("source escrow must outlive the destination escrow by the {latency}s packet timeout"),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Acknowledgement written for every received packet.
+#[cw_serde]
+pub enum IBCAck {
+    Result(AckResult),
+    Error(AckError),
+}
+
+#[cw_serde]
+pub struct AckResult {
+    /// HTLC created for a `CreateHTLC` packet
+    pub htlc_address: Option<String>,
+}
+
+/// Why the counterparty rejected a packet.
+#[cw_serde]
+pub enum AckError {
+    DuplicateSwapHash { swap_hash: String },
+    InvalidDenom { denom: String },
+    TimelockExpired { cancellation: u64 },
+    HTLCCreationFailed { msg: String },
+    Other { msg: String },
+}
+
+impl From<ContractError> for AckError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::OrderAlreadyExists { order_id } => {
+                AckError::DuplicateSwapHash { swap_hash: order_id }
+            }
+            ContractError::InvalidDenom { denom } => AckError::InvalidDenom { denom },
+            ContractError::TimelockExpired { cancellation } => {
+                AckError::TimelockExpired { cancellation }
+            }
+            err => AckError::Other { msg: err.to_string() },
+        }
+    }
+}
+
+impl fmt::Display for AckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AckError::DuplicateSwapHash { swap_hash } => write!(f, "duplicate swap hash {}", swap_hash),
+            AckError::InvalidDenom { denom } => write!(f, "invalid denom {}", denom),
+            AckError::TimelockExpired { cancellation } => {
+                write!(f, "timelock expired at {}", cancellation)
+            }
+            AckError::HTLCCreationFailed { msg } => write!(f, "HTLC creation failed: {}", msg),
+            AckError::Other { msg } => f.write_str(msg),
+        }
+    }
+}
+
+fn ack_success(htlc_address: Option<String>) -> StdResult<Binary> {
+    to_json_binary(&IBCAck::Result(AckResult { htlc_address }))
+}
+
+fn ack_fail(err: AckError) -> StdResult<Binary> {
+    to_json_binary(&IBCAck::Error(err))
+}
+
+// ================================================================================================
+// CHANNEL LIFECYCLE
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    // Interchain account channels keep the ICS-27 metadata we proposed
+    if ica::is_ica_channel(msg.channel()) {
+        ica::validate_channel(msg.channel(), msg.counterparty_version())?;
+        return Ok(None);
+    }
+
+    validate_order_and_version(msg.channel(), msg.counterparty_version())?;
+
+    Ok(Some(Ibc3ChannelOpenResponse {
+        version: IBC_VERSION.to_string(),
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    if ica::is_ica_channel(channel) {
+        ica::validate_channel(channel, msg.counterparty_version())?;
+        let version = msg.counterparty_version().unwrap_or(&channel.version);
+        return ica::channel_connect(deps, channel, version);
+    }
+
+    validate_order_and_version(channel, msg.counterparty_version())?;
+
+    let info = ChannelInfo {
+        channel_id: channel.endpoint.channel_id.clone(),
+        counterparty_endpoint: channel.counterparty_endpoint.clone(),
+        connection_id: channel.connection_id.clone(),
+    };
+    CHANNELS.save(deps.storage, info.channel_id.clone(), &info)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ibc_channel_connect")
+        .add_attribute("channel_id", info.channel_id)
+        .add_attribute("connection_id", info.connection_id))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+    if ICA_CHANNELS.has(deps.storage, channel_id.clone()) {
+        return ica::channel_close(deps, &channel_id);
+    }
+
+    CHANNELS.remove(deps.storage, channel_id.clone());
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ibc_channel_close")
+        .add_attribute("channel_id", channel_id))
+}
+
+fn validate_order_and_version(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.order != IBC_ORDERING {
+        return Err(ContractError::InvalidIBCOrdering);
+    }
+
+    if channel.version != IBC_VERSION {
+        return Err(ContractError::InvalidIBCVersion {
+            version: channel.version.clone(),
+        });
+    }
+
+    // Only set during OpenTry/OpenAck
+    if let Some(version) = counterparty_version {
+        if version != IBC_VERSION {
+            return Err(ContractError::InvalidIBCVersion {
+                version: version.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// ================================================================================================
+// PACKET LIFECYCLE
+// ================================================================================================
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, Never> {
+    // Failures become error acknowledgements so the sender learns about them
+    let res = receive_packet(deps, env, &msg.packet).and_then(|res| {
+        let ack = match res.data {
+            Some(ack) => ack,
+            None => ack_success(None)?,
+        };
+        Ok(IbcReceiveResponse::new()
+            .set_ack(ack)
+            .add_submessages(res.messages)
+            .add_attributes(res.attributes)
+            .add_events(res.events))
+    });
+
+    Ok(res.unwrap_or_else(|err| {
+        let error = err.to_string();
+        IbcReceiveResponse::new()
+            .set_ack(ack_fail(err.into()).unwrap_or_default())
+            .add_attribute("method", "ibc_packet_receive")
+            .add_attribute("error", error)
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    if ICA_CHANNELS.has(deps.storage, msg.original_packet.src.channel_id.clone()) {
+        return ica::packet_ack(deps, &env, &msg.original_packet, &msg.acknowledgement.data);
+    }
+
+    let ack: IBCAck = from_json(&msg.acknowledgement.data)?;
+    let packet: IBCPacket = from_json(&msg.original_packet.data)?;
+
+    let res = match (packet.action, ack) {
+        (IBCAction::CreateHTLC(order), IBCAck::Result(result)) => {
+            on_order_created(deps, &env, order.swap_hash, result.htlc_address)?
+        }
+        (IBCAction::CreateHTLC(order), IBCAck::Error(err)) => {
+            on_order_failed(deps, &env, order.swap_hash, err.to_string())?
+        }
+        // Status mirrors and secrets are best effort, the counterparty keeps its own record
+        // and the secret stays available on the local HTLC
+        (
+            IBCAction::UpdateOrderStatus { swap_hash, .. }
+            | IBCAction::SecretRevealed { swap_hash, .. },
+            ack,
+        ) => IbcBasicResponse::new()
+            .add_attribute("swap_hash", swap_hash)
+            .add_attribute("success", matches!(ack, IBCAck::Result(_)).to_string()),
+    };
+
+    Ok(res
+        .add_attribute("method", "ibc_packet_ack")
+        .add_attribute("sequence", msg.original_packet.sequence.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    if ICA_CHANNELS.has(deps.storage, msg.packet.src.channel_id.clone()) {
+        return ica::packet_timeout(deps, &env, &msg.packet);
+    }
+
+    let packet: IBCPacket = from_json(&msg.packet.data)?;
+
+    let res = match packet.action {
+        IBCAction::CreateHTLC(order) => {
+            on_order_failed(deps, &env, order.swap_hash, "packet timed out".to_string())?
+        }
+        IBCAction::UpdateOrderStatus { swap_hash, .. } | IBCAction::SecretRevealed { swap_hash, .. } => {
+            IbcBasicResponse::new().add_attribute("swap_hash", swap_hash)
+        }
+    };
+
+    Ok(res
+        .add_attribute("method", "ibc_packet_timeout")
+        .add_attribute("sequence", msg.packet.sequence.to_string()))
+}
+
+/// Counterparty created the HTLC: fund the local one with any held maker funds. A late
+/// confirmation can no longer fund it; the funds then go back to the maker in the reply.
+pub(crate) fn on_order_created(
+    deps: DepsMut,
+    env: &Env,
+    swap_hash: String,
+    remote_htlc_address: Option<String>,
+) -> Result<IbcBasicResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+
+    order.transition(OrderStatus::Created, Actor::Ibc, env.block.time)?;
+    order.remote_htlc_address = remote_htlc_address;
+
+    let mut messages = vec![];
+    if !order.locked_funds.is_empty() {
+        let htlc = FactoryContract(config.htlc_factory).htlc(&deps.querier, swap_hash.clone())?;
+        let lock = EscrowContract(htlc.htlc_address.clone()).call(
+            EscrowExecuteMsg::LockFunds {
+                amount: order.amount,
+                denom: order.denom.clone(),
+            },
+            std::mem::take(&mut order.locked_funds),
+        )?;
+        messages.push(SubMsg::reply_always(lock, FUND_HTLC_REPLY_ID));
+        FUNDING_ORDER.save(deps.storage, &swap_hash)?;
+        order.htlc_address = Some(htlc.htlc_address);
+    }
+
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    // Transports without acknowledgements confirm without the remote HTLC address
+    let mut res = IbcBasicResponse::new()
+        .add_submessages(messages)
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("status", "created");
+    if let Some(address) = order.remote_htlc_address {
+        res = res.add_attribute("remote_htlc_address", address);
+    }
+    Ok(res)
+}
+
+/// Counterparty rejected the order or never received it: held funds become refundable
+pub(crate) fn on_order_failed(
+    deps: DepsMut,
+    env: &Env,
+    swap_hash: String,
+    reason: String,
+) -> Result<IbcBasicResponse, ContractError> {
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+
+    order.transition(OrderStatus::Failed, Actor::Ibc, env.block.time)?;
+    order.failure_reason = Some(reason.clone());
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("status", "failed")
+        .add_attribute("reason", reason))
+}
+
+fn receive_packet(deps: DepsMut, env: Env, packet: &IbcPacket) -> Result<Response, ContractError> {
+    // Routes over other transports must not be fed from an IBC channel
+    let channel_id = &packet.dest.channel_id;
+    let route = ROUTES.idx.channel.item(deps.storage, channel_id.clone())?;
+    if !matches!(route, Some((_, Route { transport: Transport::Ibc, .. }))) {
+        return Err(ContractError::InvalidIBCChannel { channel: channel_id.clone() });
+    }
+
+    handle_packet(deps, env, channel_id, &packet.data)
+}
 
-/// Process IBC packet from other chains
+/// Process a packet of a relayed route, vouched for by the relayer set
 pub fn process_ibc_packet(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
     channel_id: String,
     packet_data: Vec<u8>,
+    attestations: Vec<Attestation>,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    
-    // Verify channel
-    if channel_id != config.ibc_channel {
+    // Routes with a light client or gateway of their own never take attested packets
+    let (_, route) = ROUTES
+        .idx
+        .channel
+        .item(deps.storage, channel_id.clone())?
+        .ok_or_else(|| ContractError::InvalidIBCChannel { channel: channel_id.clone() })?;
+    if route.transport != Transport::Relayed {
         return Err(ContractError::InvalidIBCChannel { channel: channel_id });
     }
-    
+
+    // Relaying bypasses any light client: the relayer set vouches for the packet
+    let set = RELAYER_SET
+        .may_load(deps.storage)?
+        .filter(|set| !set.relayers.is_empty())
+        .ok_or(ContractError::NoRelayerSet)?;
+    let digest = attestation_digest(&env, &route.chain_id, &channel_id, &packet_data);
+    verify_attestations(deps.api, &set, &digest, &attestations)?;
+
+    let res = handle_packet(deps, env, &channel_id, &packet_data)?;
+    Ok(res.add_attribute("transport", "relayed"))
+}
+
+/// Verify and apply a packet arriving on the route named by `channel_id`, over any transport
+pub(crate) fn handle_packet(
+    mut deps: DepsMut,
+    env: Env,
+    channel_id: &str,
+    packet_data: &[u8],
+) -> Result<Response, ContractError> {
+    // Verify channel: it must be the route of a registered chain
+    let (_, route) = ROUTES
+        .idx
+        .channel
+        .item(deps.storage, channel_id.to_string())?
+        .ok_or_else(|| ContractError::InvalidIBCChannel { channel: channel_id.to_string() })?;
+
     // Parse packet data
-    let packet: IBCPacket = serde_json::from_slice(&packet_data)
+    let packet: IBCPacket = from_json(packet_data)
         .map_err(|_| ContractError::InvalidPacketData)?;
-    
-    match packet.action {
-        IBCAction::CreateHTLC { swap_hash, maker, amount, denom, hashlock, timelock } => {
-            create_htlc_from_ibc(deps, env, info, swap_hash, maker, amount, denom, hashlock, timelock)
+
+    // Verify source: only the route's order contract may send on this channel
+    if packet.source_chain != route.chain_id || packet.source_contract != route.remote_contract {
+        return Err(ContractError::InvalidPacketSource {
+            msg: format!("{} on {}", packet.source_contract, packet.source_chain),
+        });
+    }
+
+    // Over IBC the channel itself names the sender: the counterparty port of that contract
+    if route.transport == Transport::Ibc {
+        let channel = CHANNELS
+            .may_load(deps.storage, channel_id.to_string())?
+            .ok_or_else(|| ContractError::InvalidIBCChannel { channel: channel_id.to_string() })?;
+        let port_id = &channel.counterparty_endpoint.port_id;
+        if port_id.strip_prefix("wasm.") != Some(packet.source_contract.as_str()) {
+            return Err(ContractError::InvalidPacketSource {
+                msg: format!("{} sent from port {}", packet.source_contract, port_id),
+            });
+        }
+    }
+
+    // Reject replays, whether relayed by the IBC module or by the admin
+    let key = (channel_id.to_string(), packet.sequence);
+    if RECEIVED_SEQUENCES.has(deps.storage, key.clone()) {
+        return Err(ContractError::ReplayedPacket {
+            channel: channel_id.to_string(),
+            sequence: packet.sequence,
+        });
+    }
+
+    // IBC turns a failed packet into an error ack without reverting, so handlers do every
+    // check before writing and the sequence is only recorded once the packet applied
+    let res = match packet.action {
+        IBCAction::CreateHTLC(order) => {
+            create_htlc_from_ibc(deps.branch(), env, order, route, packet.sequence)?
         }
         IBCAction::UpdateOrderStatus { swap_hash, status } => {
-            update_order_status_from_ibc(deps, swap_hash, status)
+            update_order_status_from_ibc(deps.branch(), env, swap_hash, status, packet.sequence)?
         }
-    }
+        IBCAction::SecretRevealed { swap_hash, secret } => {
+            reveal_secret_from_ibc(deps.branch(), swap_hash, secret)?
+        }
+    };
+    RECEIVED_SEQUENCES.save(deps.storage, key, &true)?;
+    Ok(res)
 }
 
-/// Send IBC packet to create HTLC on target chain
+// ================================================================================================
+// OUTGOING PACKETS
+// ================================================================================================
+
+/// Packet asking the counterparty chain to create the HTLC for an order
 pub fn send_create_htlc_ibc(
-    deps: DepsMut,
-    env: Env,
-    swap_hash: String,
-    maker: String,
-    amount: cosmwasm_std::Uint128,
-    denom: String,
-    hashlock: Vec<u8>,
-    timelock: u64,
-    target_chain: String,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    
-    let packet = IBCPacket {
-        action: IBCAction::CreateHTLC {
-            swap_hash: swap_hash.clone(),
-            maker,
-            amount,
-            denom,
-            hashlock,
-            timelock,
-        },
-    };
-    
-    let ibc_msg = IbcMsg::SendPacket {
-        channel_id: config.ibc_channel,
-        data: to_json_binary(&packet)?,
-        timeout: cosmwasm_std::IbcTimeout::with_timestamp(env.block.time.plus_seconds(300)), // 5 minutes
-    };
-    
-    Ok(Response::new()
-        .add_attribute("method", "send_create_htlc_ibc")
-        .add_attribute("swap_hash", swap_hash)
-        .add_attribute("target_chain", target_chain))
+    deps: &mut DepsMut,
+    env: &Env,
+    route: &Route,
+    order: OrderPacket,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    send_packet(deps, env, route, IBCAction::CreateHTLC(order))
 }
 
-/// Send IBC packet to update order status
+/// Packet mirroring an order status change on the counterparty chain
 pub fn send_update_status_ibc(
-    deps: DepsMut,
-    env: Env,
+    deps: &mut DepsMut,
+    env: &Env,
+    route: &Route,
     swap_hash: String,
     status: OrderStatus,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    
+) -> Result<Option<CosmosMsg>, ContractError> {
+    send_packet(deps, env, route, IBCAction::UpdateOrderStatus { swap_hash, status })
+}
+
+/// Packet carrying a secret revealed on this chain to the counterparty
+pub fn send_secret_revealed_ibc(
+    deps: &mut DepsMut,
+    env: &Env,
+    route: &Route,
+    swap_hash: String,
+    secret: Vec<u8>,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    send_packet(deps, env, route, IBCAction::SecretRevealed { swap_hash, secret })
+}
+
+fn send_packet(
+    deps: &mut DepsMut,
+    env: &Env,
+    route: &Route,
+    action: IBCAction,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    let sequence = SEND_SEQUENCES
+        .may_load(deps.storage, route.channel_id.clone())?
+        .unwrap_or_default()
+        + 1;
+    SEND_SEQUENCES.save(deps.storage, route.channel_id.clone(), &sequence)?;
+
     let packet = IBCPacket {
-        action: IBCAction::UpdateOrderStatus {
-            swap_hash: swap_hash.clone(),
-            status,
-        },
+        source_chain: env.block.chain_id.clone(),
+        source_contract: env.contract.address.to_string(),
+        sequence,
+        action,
     };
-    
-    let ibc_msg = IbcMsg::SendPacket {
-        channel_id: config.ibc_channel,
-        data: to_json_binary(&packet)?,
-        timeout: cosmwasm_std::IbcTimeout::with_timestamp(env.block.time.plus_seconds(300)),
+
+    transport::dispatch(deps.storage, env, route, sequence, to_json_binary(&packet)?)
+}
+
+/// Re-encode a bech32 account address with the counterparty chain's prefix.
+pub fn convert_address(address: &str, prefix: &str) -> Result<String, ContractError> {
+    let invalid = |e: bech32::Error| ContractError::InvalidAddress {
+        address: address.to_string(),
+        msg: e.to_string(),
     };
-    
-    Ok(Response::new()
-        .add_attribute("method", "send_update_status_ibc")
-        .add_attribute("swap_hash", swap_hash))
+    let (_, data, variant) = bech32::decode(address).map_err(invalid)?;
+    bech32::encode(prefix, data, variant).map_err(invalid)
 }
 
+// ================================================================================================
+// PACKET HANDLERS
+// ================================================================================================
+
 /// Create HTLC contract from IBC packet
 fn create_htlc_from_ibc(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
-    swap_hash: String,
-    maker: String,
-    amount: cosmwasm_std::Uint128,
-    denom: String,
-    hashlock: Vec<u8>,
-    timelock: u64,
+    packet: OrderPacket,
+    route: Route,
+    sequence: u64,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    
     // Check if order already exists
-    if ORDERS.has(deps.storage, swap_hash.clone()) {
-        return Err(ContractError::OrderAlreadyExists { order_id: swap_hash });
+    if ORDERS.has(deps.storage, packet.swap_hash.clone()) {
+        return Err(ContractError::OrderAlreadyExists { order_id: packet.swap_hash });
+    }
+
+    // Validate timelocks; the HTLC on this chain runs on the destination schedule
+    let now = env.block.time.seconds();
+    if packet.dst_timelocks.cancellation <= now {
+        return Err(ContractError::TimelockExpired { cancellation: packet.dst_timelocks.cancellation });
+    }
+    packet.validate_timelocks(now, route.timeout_seconds)?;
+    let OrderPacket { swap_hash, maker, amount, denom, hashlock, dst_timelocks: timelocks, .. } = packet;
+
+    // Validate denom: it must exist on this chain
+    if denom.is_empty() || deps.querier.query_supply(denom.clone())?.amount.is_zero() {
+        return Err(ContractError::InvalidDenom { denom });
     }
-    
+
     let maker_addr = deps.api.addr_validate(&maker)?;
-    
+    let factory = FactoryContract(config.htlc_factory);
+    let immutables = EscrowImmutables {
+        swap_hash: swap_hash.clone(),
+        mode: EscrowMode::Destination,
+        maker: maker_addr.to_string(),
+        resolver: None,
+        amount,
+        asset: Asset::Native { denom: denom.clone() },
+        hashlock: hashlock.clone(),
+        hash_algorithm: HashAlgorithm::default(),
+        parts: None,
+        timelocks: timelocks.clone(),
+        safety_deposit: None,
+    };
+    let create_htlc_msg = factory.create_escrow(immutables.clone())?;
+    let htlc_address = factory.predict_escrow_address(&deps.querier, immutables)?.address;
+
     // Create order
     let order = Order {
         swap_hash: swap_hash.clone(),
-        maker: maker_addr.clone(),
+        maker: maker_addr,
         amount,
         denom: denom.clone(),
         hashlock,
-        timelock,
-        target_chain: "unknown".to_string(),
-        status: OrderStatus::Pending,
+        timelocks: timelocks.clone(),
+        target_chain: route.chain_id.clone(),
+        // The HTLC is created in this transaction, or the order is dropped again in `reply`
+        status: OrderStatus::Created,
         created_at: env.block.time,
-        htlc_address: None,
+        htlc_address: Some(htlc_address.clone()),
+        remote_htlc_address: None,
+        locked_funds: vec![],
+        failure_reason: None,
+        secret: None,
+        ica_txs: vec![],
+        last_sequence: sequence,
+        transitions: vec![],
     };
-    
+
     ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
-    
-    // Create HTLC contract via factory
-    let create_htlc_msg = WasmMsg::Execute {
-        contract_addr: config.htlc_factory.to_string(),
-        msg: cosmwasm_std::to_json_binary(&serde_json::json!({
-            "create_h_t_l_c": {
-                "swap_hash": swap_hash.clone(),
-                "maker": maker,
-                "amount": amount.to_string(),
-                "denom": denom,
-                "hashlock": order.hashlock.clone(),
-                "timelock": timelock
-            }
-        }))?,
-        funds: vec![],
+
+    let res = match route.transport {
+        // A factory failure is turned into an error ack in `reply`
+        Transport::Ibc => {
+            RECEIVING_ORDER.save(deps.storage, &swap_hash)?;
+            Response::new()
+                .add_submessage(SubMsg::reply_always(create_htlc_msg, CREATE_HTLC_REPLY_ID))
+                .set_data(ack_success(Some(htlc_address.to_string()))?)
+        }
+        // Without acknowledgements, a failure reverts the delivery and success is confirmed
+        // with a status packet
+        _ => {
+            let confirm = send_update_status_ibc(
+                &mut deps,
+                &env,
+                &route,
+                swap_hash.clone(),
+                OrderStatus::Created,
+            )?;
+            Response::new().add_message(create_htlc_msg).add_messages(confirm)
+        }
     };
-    
-    Ok(Response::new()
-        .add_message(create_htlc_msg)
+
+    Ok(res
         .add_attribute("method", "create_htlc_from_ibc")
-        .add_attribute("swap_hash", swap_hash))
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("htlc_address", htlc_address))
+}
+
+/// Factory rejected the HTLC for an incoming order: drop the order and overwrite the ack
+pub fn reply_create_htlc_failed(deps: DepsMut, error: String) -> Result<Response, ContractError> {
+    let swap_hash = RECEIVING_ORDER.load(deps.storage)?;
+    RECEIVING_ORDER.remove(deps.storage);
+    ORDERS.remove(deps.storage, swap_hash.clone());
+
+    Ok(Response::new()
+        .set_data(ack_fail(AckError::HTLCCreationFailed { msg: error.clone() })?)
+        .add_attribute("method", "reply_create_htlc_failed")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("error", error))
+}
+
+/// The local HTLC could not be funded, e.g. its funding window closed before the counterparty
+/// confirmed: fail the order and return the held funds to the maker instead of failing the
+/// confirmation.
+pub fn reply_fund_htlc_failed(mut deps: DepsMut, env: &Env, error: String) -> Result<Response, ContractError> {
+    let swap_hash = FUNDING_ORDER.load(deps.storage)?;
+    FUNDING_ORDER.remove(deps.storage);
+
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())?;
+    order.transition(OrderStatus::Failed, Actor::Ibc, env.block.time)?;
+    order.failure_reason = Some(error.clone());
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    let refund = BankMsg::Send {
+        to_address: order.maker.to_string(),
+        amount: vec![Coin { denom: order.denom, amount: order.amount }],
+    };
+
+    // The counterparty HTLC waits for a swap that will never be funded here
+    let mut messages = vec![];
+    if let Some(route) = ROUTES.may_load(deps.storage, order.target_chain.clone())? {
+        messages.extend(send_update_status_ibc(&mut deps, env, &route, swap_hash.clone(), OrderStatus::Failed)?);
+    }
+
+    Ok(Response::new()
+        .add_message(refund)
+        .add_messages(messages)
+        .add_attribute("method", "reply_fund_htlc_failed")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("maker", order.maker)
+        .add_attribute("error", error))
 }
 
 /// Update order status from IBC packet
 fn update_order_status_from_ibc(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     swap_hash: String,
     status: OrderStatus,
+    sequence: u64,
 ) -> Result<Response, ContractError> {
     let mut order = ORDERS.load(deps.storage, swap_hash.clone())
         .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
-    
-    order.status = status.clone();
+
+    // Apply updates in the order they were sent; a late packet must not undo a newer one
+    if sequence <= order.last_sequence {
+        return Err(ContractError::StalePacket { order_id: swap_hash, sequence });
+    }
+
+    // Confirmation from a transport without acknowledgements: handle it like an ack
+    if status == OrderStatus::Created {
+        let res = on_order_created(deps.branch(), &env, swap_hash.clone(), None)?;
+        order = ORDERS.load(deps.storage, swap_hash.clone())?;
+        order.last_sequence = sequence;
+        ORDERS.save(deps.storage, swap_hash, &order)?;
+        return Ok(Response::new()
+            .add_submessages(res.messages)
+            .add_attribute("method", "update_order_status_from_ibc")
+            .add_attributes(res.attributes));
+    }
+
+    order.transition(status.clone(), Actor::Ibc, env.block.time)?;
+    order.last_sequence = sequence;
     ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
-    
+
     Ok(Response::new()
         .add_attribute("method", "update_order_status_from_ibc")
         .add_attribute("swap_hash", swap_hash)
         .add_attribute("status", format!("{:?}", status)))
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct IBCPacket {
-    action: IBCAction,
+/// Secret revealed on the counterparty chain: complete the local HTLC with it
+fn reveal_secret_from_ibc(
+    deps: DepsMut,
+    swap_hash: String,
+    secret: Vec<u8>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+
+    let htlc_address = match &order.htlc_address {
+        Some(address) => address.clone(),
+        None => FactoryContract(config.htlc_factory).htlc(&deps.querier, swap_hash.clone())?.htlc_address,
+    };
+    let escrow = EscrowContract(htlc_address.clone());
+    let swap = escrow.swap_info(&deps.querier)?;
+
+    // Verify secret against the local escrow, not the counterparty's word for it
+    if swap.parts.is_some() || swap.hash_algorithm.digest(&secret) != swap.hashlock {
+        return Err(ContractError::InvalidSecret { order_id: swap_hash });
+    }
+
+    order.secret = Some(secret.clone());
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    // Revealing can still fail (e.g. before the public withdrawal stage when this contract
+    // isn't the resolver); the secret then stays on the order for anyone to submit. The order
+    // completes in `reply_secret_revealed` once the escrow has paid out
+    let mut messages = vec![];
+    if swap.status == SwapStatus::Funded {
+        let reveal = escrow.call(EscrowExecuteMsg::RevealSecret { secret, merkle_proof: None }, vec![])?;
+        messages.push(SubMsg::reply_always(reveal, SECRET_REVEAL_REPLY_ID));
+        REVEALING_ORDER.save(deps.storage, &swap_hash)?;
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("method", "reveal_secret_from_ibc")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("htlc_address", htlc_address))
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-enum IBCAction {
-    CreateHTLC {
-        swap_hash: String,
-        maker: String,
-        amount: cosmwasm_std::Uint128,
-        denom: String,
-        hashlock: Vec<u8>,
-        timelock: u64,
-    },
-    UpdateOrderStatus {
-        swap_hash: String,
-        status: OrderStatus,
-    },
-}
\ No newline at end of file
+/// Local HTLC accepted the received secret: the swap settled, so the order completes
+pub fn reply_secret_revealed(deps: DepsMut, env: &Env) -> Result<Response, ContractError> {
+    let swap_hash = REVEALING_ORDER.load(deps.storage)?;
+    REVEALING_ORDER.remove(deps.storage);
+
+    // The factory hook may have completed it already
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())?;
+    order.complete(env.block.time)?;
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "reply_secret_revealed")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("status", format!("{:?}", order.status)))
+}
+
+/// Local HTLC rejected the received secret; the packet is still acknowledged and the order
+/// stays open with the secret on it
+pub fn reply_secret_reveal_failed(deps: DepsMut, error: String) -> Result<Response, ContractError> {
+    let swap_hash = REVEALING_ORDER.load(deps.storage)?;
+    REVEALING_ORDER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "reply_secret_reveal_failed")
+        .add_attribute("swap_hash", swap_hash)
+        .add_attribute("error", error))
+}
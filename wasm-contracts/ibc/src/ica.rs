@@ -0,0 +1,397 @@
+//! Interchain Accounts (ICS-27) controller mode.
+//!
+//! Chains that will never deploy the order contract can still host HTLCs: this contract opens
+//! an interchain account on them and drives their escrow factory with `MsgExecuteContract`
+//! transactions instead of `IBCPacket`s. Each transaction carries the order's swap hash in the
+//! packet memo so its acknowledgement can be matched back to the order.
+
+use cosmwasm_std::{
+    from_json, to_json_binary, to_json_string, Binary, Coin, CosmosMsg, DepsMut, Env,
+    IbcBasicResponse, IbcChannel, IbcMsg, IbcOrder, IbcPacket, IbcTimeout, Storage,
+};
+use htlc_msgs::escrow::ExecuteMsg as EscrowExecuteMsg;
+use htlc_msgs::factory::ExecuteMsg as FactoryExecuteMsg;
+use htlc_msgs::types::{Asset, HashAlgorithm};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+use crate::ibc::{convert_address, on_order_created, on_order_failed, OrderPacket};
+use crate::msg::{Actor, IcaAction, IcaStatus, IcaTx, IcaTxStatus, OrderStatus};
+use crate::proto::{encode_channel_open_init, encode_cosmos_tx, encode_execute_contract};
+use crate::state::{InterchainAccount, Order, ICA_CHANNELS, INTERCHAIN_ACCOUNTS, ORDERS};
+
+/// Port of the ICA host module on the counterparty chain.
+pub const ICA_HOST_PORT: &str = "icahost";
+
+/// ICS-27 application version.
+pub const ICA_VERSION: &str = "ics27-1";
+
+const ICA_ENCODING: &str = "proto3";
+const ICA_TX_TYPE: &str = "sdk_multi_msg";
+
+/// ICS-27 channels are ordered: a timed out transaction closes the channel.
+pub const ICA_ORDERING: IbcOrder = IbcOrder::Ordered;
+
+const MSG_CHANNEL_OPEN_INIT: &str = "/ibc.core.channel.v1.MsgChannelOpenInit";
+const MSG_EXECUTE_CONTRACT: &str = "/cosmwasm.wasm.v1.MsgExecuteContract";
+
+// ================================================================================================
+// ICS-27 TYPES
+// ================================================================================================
+
+/// Channel version negotiated with the ICA host; the host fills in `address`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IcaMetadata {
+    pub version: String,
+    pub controller_connection_id: String,
+    pub host_connection_id: String,
+    #[serde(default)]
+    pub address: String,
+    pub encoding: String,
+    pub tx_type: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IcaPacketData {
+    #[serde(rename = "type")]
+    pub packet_type: String,
+    /// Protobuf encoded `CosmosTx`
+    pub data: Binary,
+    #[serde(default)]
+    pub memo: String,
+}
+
+/// Identifies the order a transaction belongs to; carried in the packet memo.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IcaMemo {
+    pub swap_hash: String,
+    pub action: IcaAction,
+}
+
+/// Standard channel acknowledgement written by the ICA host.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IcaAck {
+    Result(Binary),
+    Error(String),
+}
+
+// ================================================================================================
+// REGISTRATION & CHANNEL LIFECYCLE
+// ================================================================================================
+
+/// Start the ICS-27 handshake for `account`; the channel and address are set on connect.
+pub fn register(env: &Env, account: &InterchainAccount) -> Result<CosmosMsg, ContractError> {
+    let metadata = IcaMetadata {
+        version: ICA_VERSION.to_string(),
+        controller_connection_id: account.connection_id.clone(),
+        host_connection_id: account.counterparty_connection_id.clone(),
+        address: String::new(),
+        encoding: ICA_ENCODING.to_string(),
+        tx_type: ICA_TX_TYPE.to_string(),
+    };
+
+    Ok(CosmosMsg::Stargate {
+        type_url: MSG_CHANNEL_OPEN_INIT.to_string(),
+        value: encode_channel_open_init(
+            &format!("wasm.{}", env.contract.address),
+            ICA_HOST_PORT,
+            &account.connection_id,
+            &to_json_string(&metadata)?,
+            env.contract.address.as_str(),
+        )
+        .into(),
+    })
+}
+
+/// Channels to the ICA host are handled here instead of by the order protocol.
+pub fn is_ica_channel(channel: &IbcChannel) -> bool {
+    channel.counterparty_endpoint.port_id == ICA_HOST_PORT
+}
+
+pub fn validate_channel(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.order != ICA_ORDERING {
+        return Err(ContractError::InvalidIcaChannel {
+            msg: "ICS-27 channels must be ordered".to_string(),
+        });
+    }
+
+    validate_metadata(&channel.version)?;
+    // Only set during OpenAck
+    if let Some(version) = counterparty_version {
+        validate_metadata(version)?;
+    }
+
+    Ok(())
+}
+
+fn validate_metadata(version: &str) -> Result<IcaMetadata, ContractError> {
+    let metadata: IcaMetadata = from_json(version.as_bytes())
+        .map_err(|_| ContractError::InvalidIBCVersion { version: version.to_string() })?;
+
+    if metadata.version != ICA_VERSION {
+        return Err(ContractError::InvalidIBCVersion { version: metadata.version });
+    }
+    if metadata.encoding != ICA_ENCODING || metadata.tx_type != ICA_TX_TYPE {
+        return Err(ContractError::InvalidIcaChannel {
+            msg: format!("unsupported encoding {} / tx type {}", metadata.encoding, metadata.tx_type),
+        });
+    }
+
+    Ok(metadata)
+}
+
+/// Handshake completed: record the channel and the address the host assigned.
+pub fn channel_connect(
+    deps: DepsMut,
+    channel: &IbcChannel,
+    counterparty_version: &str,
+) -> Result<IbcBasicResponse, ContractError> {
+    let metadata = validate_metadata(counterparty_version)?;
+    if metadata.address.is_empty() {
+        return Err(ContractError::InvalidIcaChannel {
+            msg: "host did not assign an account address".to_string(),
+        });
+    }
+
+    let mut account = INTERCHAIN_ACCOUNTS
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .filter_map(|item| item.ok().map(|(_, account)| account))
+        .find(|account| {
+            account.connection_id == channel.connection_id && account.status == IcaStatus::Opening
+        })
+        .ok_or_else(|| ContractError::InvalidIcaChannel {
+            msg: format!("no account registration pending on {}", channel.connection_id),
+        })?;
+
+    let channel_id = channel.endpoint.channel_id.clone();
+    account.status = IcaStatus::Open;
+    account.channel_id = Some(channel_id.clone());
+    account.address = Some(metadata.address.clone());
+    INTERCHAIN_ACCOUNTS.save(deps.storage, account.chain_id.clone(), &account)?;
+    ICA_CHANNELS.save(deps.storage, channel_id.clone(), &account.chain_id)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ica_channel_connect")
+        .add_attribute("chain_id", account.chain_id)
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("ica_address", metadata.address))
+}
+
+/// The account stays registered on the host; registering again reopens a channel to it.
+pub fn channel_close(deps: DepsMut, channel_id: &str) -> Result<IbcBasicResponse, ContractError> {
+    let chain_id = close_account(deps.storage, channel_id)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ica_channel_close")
+        .add_attribute("chain_id", chain_id)
+        .add_attribute("channel_id", channel_id))
+}
+
+fn close_account(storage: &mut dyn Storage, channel_id: &str) -> Result<String, ContractError> {
+    let chain_id = ICA_CHANNELS.load(storage, channel_id.to_string())?;
+    ICA_CHANNELS.remove(storage, channel_id.to_string());
+
+    let mut account = INTERCHAIN_ACCOUNTS.load(storage, chain_id.clone())?;
+    account.status = IcaStatus::Closed;
+    account.channel_id = None;
+    INTERCHAIN_ACCOUNTS.save(storage, chain_id.clone(), &account)?;
+
+    Ok(chain_id)
+}
+
+/// Load the account for `chain_id`, requiring an open channel to it.
+pub fn open_account(storage: &dyn Storage, chain_id: &str) -> Result<InterchainAccount, ContractError> {
+    let account = INTERCHAIN_ACCOUNTS
+        .may_load(storage, chain_id.to_string())?
+        .ok_or_else(|| ContractError::UnknownChain { chain_id: chain_id.to_string() })?;
+
+    if account.status != IcaStatus::Open || account.channel_id.is_none() || account.address.is_none() {
+        return Err(ContractError::InterchainAccountNotReady { chain_id: chain_id.to_string() });
+    }
+
+    Ok(account)
+}
+
+// ================================================================================================
+// OUTGOING TRANSACTIONS
+// ================================================================================================
+
+/// Create the order's HTLC on the remote factory, owned by the maker's counterparty address.
+pub fn create_remote_htlc(
+    env: &Env,
+    account: &InterchainAccount,
+    order: OrderPacket,
+) -> Result<(IbcMsg, IcaTx), ContractError> {
+    let ica_address = account.address.clone().unwrap_or_default();
+    let (prefix, _, _) = bech32::decode(&ica_address).map_err(|e| ContractError::InvalidAddress {
+        address: ica_address.clone(),
+        msg: e.to_string(),
+    })?;
+
+    let msg = FactoryExecuteMsg::CreateHTLC {
+        swap_hash: order.swap_hash.clone(),
+        maker: convert_address(&order.maker, &prefix)?,
+        resolver: None,
+        amount: order.amount,
+        asset: Asset::Native { denom: order.denom },
+        hashlock: order.hashlock,
+        hash_algorithm: HashAlgorithm::default(),
+        parts: None,
+        timelocks: order.dst_timelocks,
+        safety_deposit: None,
+    };
+
+    send_tx(env, account, order.swap_hash, IcaAction::Create, &account.remote_factory, to_json_binary(&msg)?, vec![])
+}
+
+/// Fund the remote HTLC from the interchain account's balance; the account becomes resolver.
+pub fn fund_remote_htlc(
+    env: &Env,
+    account: &InterchainAccount,
+    order: &Order,
+    htlc_address: &str,
+) -> Result<(IbcMsg, IcaTx), ContractError> {
+    let msg = EscrowExecuteMsg::LockFunds {
+        amount: order.amount,
+        denom: order.denom.clone(),
+    };
+    let funds = vec![Coin {
+        denom: order.denom.clone(),
+        amount: order.amount,
+    }];
+
+    send_tx(env, account, order.swap_hash.clone(), IcaAction::Fund, htlc_address, to_json_binary(&msg)?, funds)
+}
+
+/// Cancel the remote HTLC, returning its funds to the interchain account.
+pub fn cancel_remote_htlc(
+    env: &Env,
+    account: &InterchainAccount,
+    order: &Order,
+    htlc_address: &str,
+) -> Result<(IbcMsg, IcaTx), ContractError> {
+    let msg = EscrowExecuteMsg::CancelSwap {};
+
+    send_tx(env, account, order.swap_hash.clone(), IcaAction::Cancel, htlc_address, to_json_binary(&msg)?, vec![])
+}
+
+fn send_tx(
+    env: &Env,
+    account: &InterchainAccount,
+    swap_hash: String,
+    action: IcaAction,
+    contract: &str,
+    msg: Binary,
+    funds: Vec<Coin>,
+) -> Result<(IbcMsg, IcaTx), ContractError> {
+    let (Some(channel_id), Some(sender)) = (&account.channel_id, &account.address) else {
+        return Err(ContractError::InterchainAccountNotReady { chain_id: account.chain_id.clone() });
+    };
+
+    let execute = encode_execute_contract(sender, contract, &msg, &funds);
+    let packet = IcaPacketData {
+        packet_type: "TYPE_EXECUTE_TX".to_string(),
+        data: encode_cosmos_tx(&[(MSG_EXECUTE_CONTRACT, execute)]).into(),
+        memo: to_json_string(&IcaMemo { swap_hash, action: action.clone() })?,
+    };
+
+    let ibc_msg = IbcMsg::SendPacket {
+        channel_id: channel_id.clone(),
+        data: to_json_binary(&packet)?,
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(account.timeout_seconds)),
+    };
+    let tx = IcaTx {
+        action,
+        status: IcaTxStatus::Pending,
+        error: None,
+        sent_at: env.block.time,
+    };
+
+    Ok((ibc_msg, tx))
+}
+
+// ================================================================================================
+// ACKNOWLEDGEMENTS
+// ================================================================================================
+
+pub fn packet_ack(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+    ack: &Binary,
+) -> Result<IbcBasicResponse, ContractError> {
+    let error = match from_json::<IcaAck>(ack)? {
+        IcaAck::Result(_) => None,
+        IcaAck::Error(error) => Some(error),
+    };
+    on_tx_result(deps, env, packet, error)
+}
+
+/// A timeout closes the ordered channel, so the account must be registered again.
+pub fn packet_timeout(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+) -> Result<IbcBasicResponse, ContractError> {
+    close_account(deps.storage, &packet.src.channel_id)?;
+    on_tx_result(deps, env, packet, Some("packet timed out".to_string()))
+}
+
+fn on_tx_result(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+    error: Option<String>,
+) -> Result<IbcBasicResponse, ContractError> {
+    let data: IcaPacketData = from_json(&packet.data)?;
+    let IcaMemo { swap_hash, action } = from_json(data.memo.as_bytes())?;
+
+    // Record the outcome on the oldest pending transaction of this kind
+    let mut order = ORDERS.load(deps.storage, swap_hash.clone())
+        .map_err(|_| ContractError::OrderNotFound { order_id: swap_hash.clone() })?;
+    if let Some(tx) = order
+        .ica_txs
+        .iter_mut()
+        .find(|tx| tx.action == action && tx.status == IcaTxStatus::Pending)
+    {
+        tx.status = match error {
+            None => IcaTxStatus::Succeeded,
+            Some(_) => IcaTxStatus::Failed,
+        };
+        tx.error = error.clone();
+    }
+    ORDERS.save(deps.storage, swap_hash.clone(), &order)?;
+
+    let res = match (action, error) {
+        (IcaAction::Create, None) => on_order_created(deps, env, swap_hash.clone(), None),
+        (IcaAction::Create, Some(error)) => on_order_failed(deps, env, swap_hash.clone(), error),
+        (IcaAction::Fund, None) => set_status(deps, env, &swap_hash, OrderStatus::Funded),
+        (IcaAction::Cancel, None) => set_status(deps, env, &swap_hash, OrderStatus::Cancelled),
+        // Failed funding or cancellation leaves the order as it was; it can be retried
+        (_, Some(error)) => Ok(IbcBasicResponse::new().add_attribute("error", error)),
+    };
+    // The channel is ordered: failing here would block every later transaction, so an
+    // outcome the order can no longer accept is only reported
+    let res = res.unwrap_or_else(|err| IbcBasicResponse::new().add_attribute("error", err.to_string()));
+
+    Ok(res
+        .add_attribute("method", "ica_packet_result")
+        .add_attribute("swap_hash", swap_hash))
+}
+
+fn set_status(
+    deps: DepsMut,
+    env: &Env,
+    swap_hash: &str,
+    status: OrderStatus,
+) -> Result<IbcBasicResponse, ContractError> {
+    let mut order = ORDERS.load(deps.storage, swap_hash.to_string())?;
+    order.transition(status.clone(), Actor::Ibc, env.block.time)?;
+    ORDERS.save(deps.storage, swap_hash.to_string(), &order)?;
+
+    Ok(IbcBasicResponse::new().add_attribute("status", format!("{:?}", status)))
+}
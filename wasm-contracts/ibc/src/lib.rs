@@ -1,8 +1,12 @@
 pub mod contract;
 pub mod error;
 pub mod ibc;
+pub mod ica;
 pub mod msg;
+pub mod proto;
+pub mod relayer;
 pub mod state;
+pub mod transport;
 
 pub use contract::{execute, instantiate, query};
 pub use error::ContractError;
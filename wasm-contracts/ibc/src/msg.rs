@@ -1,36 +1,81 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128, Timestamp};
+use cosmwasm_std::{Addr, Binary, Coin, HexBinary, Uint128, Timestamp};
+use htlc_msgs::types::SwapStatus;
+
+pub use htlc_msgs::types::Timelocks;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub admin: String,
     pub htlc_factory: String,
-    pub ibc_channel: String,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    // Create order and HTLC
+    // Create order and its source HTLC, withdrawable by `resolver`; only the maker may. Funds
+    // attached by the maker (exactly `amount` of `denom`) are held until the counterparty
+    // acknowledges the remote HTLC, then lock the local one; if that fails they are returned to
+    // the maker. The route's transport fee, if any, is attached on top. Orders to an
+    // interchain account chain ignore `resolver`: their source HTLC pays this contract, which
+    // funds the remote HTLC from the account.
     CreateOrder {
         swap_hash: String,
         maker: String,
+        resolver: String,
         amount: Uint128,
         denom: String,
         hashlock: Vec<u8>,
-        timelock: u64,
+        // Timelocks of the maker's source HTLC on this chain
+        timelocks: Timelocks,
+        // Timelocks of the HTLC on the target chain; it must unlock no later than the source
+        // HTLC and become cancellable at least one route packet timeout earlier
+        dst_timelocks: Timelocks,
         target_chain: String,
     },
     
-    // Update order status (called by HTLC contracts)
+    // Report an order status change; the transition must be legal for the sender's role
+    // (maker, resolver of the local HTLC or HTLC factory). The sender attaches the transport
+    // fee of the route mirroring it.
     UpdateOrderStatus {
         swap_hash: String,
         status: OrderStatus,
     },
     
-    // IBC packet handling
+    // Secret revealed on this chain for an order, from the escrow factory's secret hook or
+    // anyone holding it; forwarded along the order's route to complete the counterpart escrow
+    SecretRevealed {
+        swap_hash: String,
+        secret: Vec<u8>,
+    },
+
+    // Status change from the escrow factory's secret hook, applied to the order of the escrow
+    // on behalf of the factory
+    StatusChanged {
+        swap_hash: String,
+        status: SwapStatus,
+    },
+
+    // Return funds held for a failed order to its maker
+    RefundOrder {
+        swap_hash: String,
+    },
+
+    // Deliver a packet on a relayed route, named by its virtual `channel_id`. Requires
+    // `threshold` attestations from the configured relayer set.
     ProcessIBCPacket {
         channel_id: String,
         packet_data: Vec<u8>,
+        #[serde(default)]
+        attestations: Vec<Attestation>,
+    },
+
+    // Drop a delivered packet from the outbox of a relayed route. Requires `threshold`
+    // attestations over its receipt digest (see `relayer::receipt_digest`).
+    AcknowledgeRelayPacket {
+        channel_id: String,
+        sequence: u64,
+        #[serde(default)]
+        attestations: Vec<Attestation>,
     },
     
     // Admin functions
@@ -38,10 +83,65 @@ pub enum ExecuteMsg {
         htlc_factory: String,
     },
     
-    UpdateIBCChannel {
+    // Register or replace the route to a counterparty chain. With a non-IBC `transport`,
+    // `channel_id` is a virtual id naming the route.
+    SetRoute {
+        chain_id: String,
         channel_id: String,
+        #[serde(default)]
+        transport: Transport,
+        // Order contract on the counterparty chain; packets must name it as their source
+        remote_contract: String,
+        remote_factory: String,
+        address_prefix: String,
+        timeout_seconds: u64,
+    },
+
+    RemoveRoute {
+        chain_id: String,
+    },
+
+    // Open an interchain account on a chain without an order contract. Orders to `chain_id`
+    // are then created on `remote_factory` by MsgExecuteContract from that account.
+    RegisterInterchainAccount {
+        chain_id: String,
+        connection_id: String,
+        counterparty_connection_id: String,
+        remote_factory: String,
+        timeout_seconds: u64,
+    },
+
+    // Fund the remote HTLC of an interchain account order from the account's balance
+    FundRemoteHTLC {
+        swap_hash: String,
+        htlc_address: String,
+    },
+
+    // Cancel the remote HTLC of an interchain account order, refunding the account
+    CancelRemoteHTLC {
+        swap_hash: String,
     },
     
+    // Axelar GMP message, executed by ibc-hooks from the Axelar channel
+    ReceiveGmp {
+        source_chain: String,
+        source_address: String,
+        payload: Binary,
+    },
+
+    // Hyperlane message delivered by the mailbox (`IMessageRecipient::handle`)
+    Handle {
+        origin: u32,
+        sender: HexBinary,
+        body: HexBinary,
+    },
+
+    // Replace the relayer set; relayed routes accept no packets while it is empty
+    UpdateRelayerSet {
+        relayers: Vec<RelayerKey>,
+        threshold: u32,
+    },
+
     UpdateAdmin {
         admin: String,
     },
@@ -56,6 +156,29 @@ pub enum QueryMsg {
     #[returns(Vec<OrderInfo>)]
     GetOrdersByMaker { maker: String },
     
+    #[returns(RouteInfo)]
+    GetRoute { chain_id: String },
+
+    #[returns(Vec<RouteInfo>)]
+    ListRoutes {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(InterchainAccountInfo)]
+    GetInterchainAccount { chain_id: String },
+
+    #[returns(RelayerSetResponse)]
+    GetRelayerSet {},
+
+    // Outgoing packets of a relayed route, for relayers to pick up
+    #[returns(Vec<RelayPacket>)]
+    ListRelayPackets {
+        channel_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
     #[returns(ConfigResponse)]
     GetConfig {},
 }
@@ -67,10 +190,19 @@ pub struct OrderInfo {
     pub amount: Uint128,
     pub denom: String,
     pub hashlock: Vec<u8>,
-    pub timelock: u64,
+    pub timelocks: Timelocks,
+    // Counterparty chain: the destination of outgoing orders, the source of incoming ones
     pub target_chain: String,
     pub htlc_address: Option<Addr>,
+    pub remote_htlc_address: Option<String>,
+    pub locked_funds: Vec<Coin>,
     pub status: OrderStatus,
+    pub failure_reason: Option<String>,
+    // Swap secret, once revealed on either chain
+    pub secret: Option<Vec<u8>>,
+    // Transactions sent through an interchain account for this order
+    pub ica_txs: Vec<IcaTx>,
+    pub transitions: Vec<OrderTransition>,
     pub created_at: Timestamp,
 }
 
@@ -79,14 +211,159 @@ pub enum OrderStatus {
     Pending,
     Created,
     Funded,
+    // Secret verified on either chain, settling the swap
     Completed,
     Cancelled,
+    // Never stored: reported by queries once an open order passes its cancellation timelock
     Expired,
+    // Counterparty rejected the order or the packet timed out
+    Failed,
+}
+
+// Who moved an order to a new status
+#[cw_serde]
+pub enum Actor {
+    Maker,
+    Resolver,
+    Factory,
+    // Acknowledgements, timeouts and packets from the counterparty chain
+    Ibc,
+}
+
+#[cw_serde]
+pub struct OrderTransition {
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+    pub actor: Actor,
+    pub at: Timestamp,
 }
 
 #[cw_serde]
 pub struct ConfigResponse {
     pub admin: Addr,
     pub htlc_factory: Addr,
-    pub ibc_channel: String,
-}
\ No newline at end of file
+}
+
+#[cw_serde]
+pub struct RouteInfo {
+    pub chain_id: String,
+    pub channel_id: String,
+    pub transport: Transport,
+    pub remote_contract: String,
+    pub remote_factory: String,
+    pub address_prefix: String,
+    pub timeout_seconds: u64,
+}
+
+#[cw_serde]
+pub struct RelayPacket {
+    pub sequence: u64,
+    pub data: Binary,
+}
+
+#[cw_serde]
+pub struct InterchainAccountInfo {
+    pub chain_id: String,
+    pub connection_id: String,
+    pub counterparty_connection_id: String,
+    pub remote_factory: String,
+    pub timeout_seconds: u64,
+    pub status: IcaStatus,
+    pub channel_id: Option<String>,
+    pub address: Option<String>,
+}
+
+#[cw_serde]
+pub enum IcaStatus {
+    // Channel handshake in flight
+    Opening,
+    Open,
+    // Channel closed, e.g. after a timeout; registering again reopens it
+    Closed,
+}
+
+#[cw_serde]
+pub enum IcaAction {
+    Create,
+    Fund,
+    Cancel,
+}
+
+#[cw_serde]
+pub struct IcaTx {
+    pub action: IcaAction,
+    pub status: IcaTxStatus,
+    pub error: Option<String>,
+    pub sent_at: Timestamp,
+}
+
+#[cw_serde]
+pub enum IcaTxStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+#[cw_serde]
+pub enum KeyType {
+    // Compressed or uncompressed SEC1 public key
+    Secp256k1,
+    Ed25519,
+}
+
+#[cw_serde]
+pub struct RelayerKey {
+    pub key_type: KeyType,
+    pub public_key: Binary,
+}
+
+// Relayer signature over the attestation digest (see `relayer::attestation_digest`);
+// secp256k1 signatures are 64-byte compact (r, s)
+#[cw_serde]
+pub struct Attestation {
+    pub public_key: Binary,
+    pub signature: Binary,
+}
+
+#[cw_serde]
+pub struct RelayerSetResponse {
+    pub relayers: Vec<RelayerKey>,
+    pub threshold: u32,
+}
+
+// How packets travel to and from a counterparty chain
+#[cw_serde]
+#[derive(Default)]
+pub enum Transport {
+    // Native IBC over the route's channel
+    #[default]
+    Ibc,
+    // Axelar GMP: packets ride an ICS-20 transfer of `fee` to the Axelar GMP account, with the
+    // destination in the transfer memo. Inbound messages arrive through ibc-hooks.
+    Axelar {
+        // ICS-20 channel to Axelar
+        channel_id: String,
+        // Axelar GMP account receiving the transfer
+        gateway: String,
+        // Name Axelar knows the counterparty chain by
+        destination_chain: String,
+        // Transferred with each packet; paid by the sender of the message that sends it
+        fee: Coin,
+        fee_recipient: String,
+        // ibc-hooks sender of inbound messages on the Axelar channel
+        inbound_sender: String,
+    },
+    // Hyperlane: packets are dispatched through the local mailbox, which also delivers
+    // inbound messages
+    Hyperlane {
+        mailbox: String,
+        // Hyperlane domain of the counterparty chain
+        domain: u32,
+        // Interchain gas payment attached to each dispatch, paid like the Axelar fee
+        fee: Option<Coin>,
+    },
+    // Relayer set: outgoing packets are queued for relayers until they attest delivery with
+    // `AcknowledgeRelayPacket`; they deliver inbound packets with `ProcessIBCPacket` and
+    // attestations
+    Relayed,
+}
@@ -0,0 +1,106 @@
+//! Just enough proto3 to build the Cosmos SDK messages this contract sends as `Stargate`
+//! messages or inside interchain account transactions, without a protobuf dependency.
+//! Fields holding default values are omitted, as proto3 encoders do.
+
+use cosmwasm_std::Coin;
+
+fn encode_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn encode_varint_field(field: u32, value: u64, buf: &mut Vec<u8>) {
+    if value != 0 {
+        encode_varint(u64::from(field << 3), buf);
+        encode_varint(value, buf);
+    }
+}
+
+fn encode_bytes_field(field: u32, value: &[u8], buf: &mut Vec<u8>) {
+    if !value.is_empty() {
+        encode_varint(u64::from((field << 3) | 2), buf);
+        encode_varint(value.len() as u64, buf);
+        buf.extend_from_slice(value);
+    }
+}
+
+/// `cosmos.base.v1beta1.Coin`
+fn encode_coin(coin: &Coin) -> Vec<u8> {
+    let mut buf = vec![];
+    encode_bytes_field(1, coin.denom.as_bytes(), &mut buf);
+    encode_bytes_field(2, coin.amount.to_string().as_bytes(), &mut buf);
+    buf
+}
+
+/// `ibc.core.channel.v1.MsgChannelOpenInit` for an ordered channel to `counterparty_port`.
+pub fn encode_channel_open_init(
+    port_id: &str,
+    counterparty_port: &str,
+    connection_id: &str,
+    version: &str,
+    signer: &str,
+) -> Vec<u8> {
+    let mut counterparty = vec![];
+    encode_bytes_field(1, counterparty_port.as_bytes(), &mut counterparty);
+
+    let mut channel = vec![];
+    encode_varint_field(1, 1, &mut channel); // STATE_INIT
+    encode_varint_field(2, 2, &mut channel); // ORDER_ORDERED
+    encode_bytes_field(3, &counterparty, &mut channel);
+    encode_bytes_field(4, connection_id.as_bytes(), &mut channel);
+    encode_bytes_field(5, version.as_bytes(), &mut channel);
+
+    let mut msg = vec![];
+    encode_bytes_field(1, port_id.as_bytes(), &mut msg);
+    encode_bytes_field(2, &channel, &mut msg);
+    encode_bytes_field(3, signer.as_bytes(), &mut msg);
+    msg
+}
+
+/// `cosmwasm.wasm.v1.MsgExecuteContract`
+pub fn encode_execute_contract(sender: &str, contract: &str, msg: &[u8], funds: &[Coin]) -> Vec<u8> {
+    let mut buf = vec![];
+    encode_bytes_field(1, sender.as_bytes(), &mut buf);
+    encode_bytes_field(2, contract.as_bytes(), &mut buf);
+    encode_bytes_field(3, msg, &mut buf);
+    for coin in funds {
+        encode_bytes_field(5, &encode_coin(coin), &mut buf);
+    }
+    buf
+}
+
+/// `ibc.applications.interchain_accounts.v1.CosmosTx` wrapping each message in an `Any`.
+pub fn encode_cosmos_tx(messages: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut buf = vec![];
+    for (type_url, value) in messages {
+        let mut any = vec![];
+        encode_bytes_field(1, type_url.as_bytes(), &mut any);
+        encode_bytes_field(2, value, &mut any);
+        encode_bytes_field(1, &any, &mut buf);
+    }
+    buf
+}
+
+/// `ibc.applications.transfer.v1.MsgTransfer` with a timestamp timeout and a memo, which
+/// `IbcMsg::Transfer` can't carry.
+pub fn encode_msg_transfer(
+    source_channel: &str,
+    token: &Coin,
+    sender: &str,
+    receiver: &str,
+    timeout_timestamp: u64,
+    memo: &str,
+) -> Vec<u8> {
+    let mut buf = vec![];
+    encode_bytes_field(1, b"transfer", &mut buf);
+    encode_bytes_field(2, source_channel.as_bytes(), &mut buf);
+    encode_bytes_field(3, &encode_coin(token), &mut buf);
+    encode_bytes_field(4, sender.as_bytes(), &mut buf);
+    encode_bytes_field(5, receiver.as_bytes(), &mut buf);
+    encode_varint_field(7, timeout_timestamp, &mut buf);
+    encode_bytes_field(8, memo.as_bytes(), &mut buf);
+    buf
+}
@@ -0,0 +1,139 @@
+//! Relayer attestations for packets of relayed routes, delivered with `ProcessIBCPacket`.
+//!
+//! Chain pairs without an IBC connection have no light client to prove a packet was sent, so
+//! a configured set of relayers vouches for it instead: at least `threshold` distinct relayers
+//! must sign the attestation digest of the packet.
+
+use std::collections::HashSet;
+
+use cosmwasm_std::{Api, Env};
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::msg::{Attestation, KeyType};
+use crate::state::RelayerSet;
+
+/// Domain separator, so attestations can't be reused as signatures for anything else.
+pub const ATTESTATION_DOMAIN: &[u8] = b"htlc-orders-attestation-v1";
+
+/// Domain separator of delivery receipts for packets this contract queued.
+pub const RECEIPT_DOMAIN: &[u8] = b"htlc-orders-receipt-v1";
+
+/// sha256 over the domain and each field prefixed with its big-endian u32 length:
+/// destination chain id, destination contract, source chain id, channel id, packet bytes.
+/// Binding the destination prevents replaying attestations against another deployment.
+pub fn attestation_digest(env: &Env, source_chain: &str, channel_id: &str, packet_data: &[u8]) -> Vec<u8> {
+    digest(
+        ATTESTATION_DOMAIN,
+        &[
+            env.block.chain_id.as_bytes(),
+            env.contract.address.as_bytes(),
+            source_chain.as_bytes(),
+            channel_id.as_bytes(),
+            packet_data,
+        ],
+    )
+}
+
+/// Digest relayers sign once packet `sequence` of `channel_id` was delivered, laid out like
+/// `attestation_digest`: source chain id, source contract, channel id, big-endian sequence.
+pub fn receipt_digest(env: &Env, channel_id: &str, sequence: u64) -> Vec<u8> {
+    digest(
+        RECEIPT_DOMAIN,
+        &[
+            env.block.chain_id.as_bytes(),
+            env.contract.address.as_bytes(),
+            channel_id.as_bytes(),
+            &sequence.to_be_bytes(),
+        ],
+    )
+}
+
+fn digest(domain: &[u8], fields: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    for field in fields {
+        hasher.update((field.len() as u32).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.finalize().to_vec()
+}
+
+/// Require `threshold` valid signatures over `digest` from distinct relayers in `set`.
+pub fn verify_attestations(
+    api: &dyn Api,
+    set: &RelayerSet,
+    digest: &[u8],
+    attestations: &[Attestation],
+) -> Result<(), ContractError> {
+    let mut signers = HashSet::new();
+
+    for attestation in attestations {
+        // Signatures from unknown keys or repeated signers don't count
+        let Some(relayer) = set.relayers.iter().find(|r| r.public_key == attestation.public_key) else {
+            continue;
+        };
+        if signers.contains(&relayer.public_key) {
+            continue;
+        }
+
+        let valid = match relayer.key_type {
+            KeyType::Secp256k1 => api.secp256k1_verify(digest, &attestation.signature, &relayer.public_key),
+            KeyType::Ed25519 => api.ed25519_verify(digest, &attestation.signature, &relayer.public_key),
+        }
+        .unwrap_or(false);
+
+        if valid {
+            signers.insert(relayer.public_key.clone());
+        }
+    }
+
+    let valid = signers.len() as u32;
+    if valid < set.threshold {
+        return Err(ContractError::InsufficientAttestations {
+            valid,
+            threshold: set.threshold,
+        });
+    }
+
+    Ok(())
+}
+
+/// Keys must be unique and well formed, and the threshold reachable. An empty set with a zero
+/// threshold disables attestations.
+pub fn validate_relayer_set(set: &RelayerSet) -> Result<(), ContractError> {
+    if set.relayers.is_empty() {
+        if set.threshold != 0 {
+            return Err(ContractError::InvalidRelayerSet {
+                msg: "Threshold must be zero without relayers".to_string(),
+            });
+        }
+        return Ok(());
+    }
+
+    if set.threshold == 0 || set.threshold as usize > set.relayers.len() {
+        return Err(ContractError::InvalidRelayerSet {
+            msg: format!("Threshold must be between 1 and {}", set.relayers.len()),
+        });
+    }
+
+    let mut keys = HashSet::new();
+    for relayer in &set.relayers {
+        let valid_length = match relayer.key_type {
+            KeyType::Secp256k1 => matches!(relayer.public_key.len(), 33 | 65),
+            KeyType::Ed25519 => relayer.public_key.len() == 32,
+        };
+        if !valid_length {
+            return Err(ContractError::InvalidRelayerSet {
+                msg: format!("Invalid {:?} public key {}", relayer.key_type, relayer.public_key),
+            });
+        }
+        if !keys.insert(relayer.public_key.clone()) {
+            return Err(ContractError::InvalidRelayerSet {
+                msg: format!("Duplicate public key {}", relayer.public_key),
+            });
+        }
+    }
+
+    Ok(())
+}
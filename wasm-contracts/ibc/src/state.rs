@@ -1,15 +1,48 @@
-use cosmwasm_std::{Addr, Uint128, Timestamp};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Coin, IbcEndpoint, Uint128, Timestamp};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, UniqueIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::OrderStatus;
+use crate::error::ContractError;
+use crate::transport::route_source;
+use crate::msg::{
+    Actor, IcaStatus, IcaTx, OrderStatus, OrderTransition, RelayerKey, Timelocks, Transport,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub admin: Addr,
     pub htlc_factory: Addr,
-    pub ibc_channel: String,
+}
+
+/// Counterparty chain reachable from this contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Route {
+    pub chain_id: String,
+    /// IBC channel, or a virtual id for other transports
+    pub channel_id: String,
+    pub transport: Transport,
+    /// Order contract on the counterparty chain, the only accepted packet source
+    pub remote_contract: String,
+    /// Escrow factory on the counterparty chain
+    pub remote_factory: String,
+    /// Bech32 prefix of account addresses on the counterparty chain
+    pub address_prefix: String,
+    pub timeout_seconds: u64,
+}
+
+pub struct RouteIndexes<'a> {
+    /// Each channel leads to exactly one chain
+    pub channel: UniqueIndex<'a, String, Route, String>,
+    /// Inbound messages name their transport and source, which lead to exactly one chain
+    pub source: UniqueIndex<'a, (String, String), Route, String>,
+}
+
+impl<'a> IndexList<Route> for RouteIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Route>> + '_> {
+        let v: Vec<&dyn Index<Route>> = vec![&self.channel, &self.source];
+        Box::new(v.into_iter())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -19,14 +52,156 @@ pub struct Order {
     pub amount: Uint128,
     pub denom: String,
     pub hashlock: Vec<u8>,
-    pub timelock: u64,
+    /// Timelocks of the HTLC on this chain: the source schedule for outgoing orders, the
+    /// destination schedule for incoming ones
+    pub timelocks: Timelocks,
     pub target_chain: String,
     pub htlc_address: Option<Addr>,
+    /// HTLC created for the order on the counterparty chain, from its acknowledgement
+    pub remote_htlc_address: Option<String>,
+    /// Maker funds held by this contract until the counterparty confirms the order
+    pub locked_funds: Vec<Coin>,
     pub status: OrderStatus,
+    pub failure_reason: Option<String>,
+    /// Swap secret, once revealed on either chain
+    pub secret: Option<Vec<u8>>,
+    /// Transactions sent through an interchain account, oldest first
+    pub ica_txs: Vec<IcaTx>,
+    /// Sequence of the last packet from the counterparty applied to this order
+    pub last_sequence: u64,
+    /// Every status change, oldest first
+    pub transitions: Vec<OrderTransition>,
     pub created_at: Timestamp,
 }
 
+impl Order {
+    /// Move to `to` if `actor` may make that transition, recording it at `at`.
+    pub fn transition(&mut self, to: OrderStatus, actor: Actor, at: Timestamp) -> Result<(), ContractError> {
+        if !self.status.can_transition(&to, &actor) {
+            return Err(ContractError::InvalidStatusTransition {
+                from: format!("{:?}", self.status),
+                to: format!("{:?}", to),
+                actor: format!("{:?}", actor),
+            });
+        }
+
+        self.transitions.push(OrderTransition {
+            from: self.status.clone(),
+            to: to.clone(),
+            actor,
+            at,
+        });
+        self.status = to;
+        Ok(())
+    }
+
+    /// Settle an open order once its local HTLC paid out with the secret.
+    pub fn complete(&mut self, at: Timestamp) -> Result<(), ContractError> {
+        if matches!(self.status, OrderStatus::Created | OrderStatus::Funded) {
+            self.transition(OrderStatus::Completed, Actor::Ibc, at)?;
+        }
+        Ok(())
+    }
+
+    /// Status as reported to clients: open orders past their cancellation timelock are expired.
+    pub fn status_at(&self, now: Timestamp) -> OrderStatus {
+        let open = matches!(self.status, OrderStatus::Pending | OrderStatus::Created | OrderStatus::Funded);
+        if open && now.seconds() >= self.timelocks.cancellation {
+            OrderStatus::Expired
+        } else {
+            self.status.clone()
+        }
+    }
+}
+
+impl OrderStatus {
+    /// Legal order lifecycle. Completed, Cancelled and Failed are final; Expired is derived.
+    pub fn can_transition(&self, to: &OrderStatus, actor: &Actor) -> bool {
+        match (self, to) {
+            (OrderStatus::Pending, OrderStatus::Created | OrderStatus::Failed) => *actor == Actor::Ibc,
+            (OrderStatus::Created, OrderStatus::Funded) => {
+                matches!(actor, Actor::Resolver | Actor::Factory | Actor::Ibc)
+            }
+            // The maker may withdraw an order nobody has funded yet
+            (OrderStatus::Created, OrderStatus::Cancelled) => {
+                matches!(actor, Actor::Maker | Actor::Factory | Actor::Ibc)
+            }
+            (OrderStatus::Funded, OrderStatus::Completed | OrderStatus::Cancelled) => {
+                matches!(actor, Actor::Factory | Actor::Ibc)
+            }
+            // The maker's held funds could not be locked once the counterparty confirmed
+            (OrderStatus::Created, OrderStatus::Failed) => *actor == Actor::Ibc,
+            // A verified secret settles the swap even if funding was never reported
+            (OrderStatus::Created, OrderStatus::Completed) => *actor == Actor::Ibc,
+            _ => false,
+        }
+    }
+}
+
+/// Interchain account controlled on a chain without an order contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InterchainAccount {
+    pub chain_id: String,
+    pub connection_id: String,
+    pub counterparty_connection_id: String,
+    /// Escrow factory on the host chain
+    pub remote_factory: String,
+    pub timeout_seconds: u64,
+    pub status: IcaStatus,
+    /// Set once the channel handshake completes
+    pub channel_id: Option<String>,
+    pub address: Option<String>,
+}
+
+/// Relayers allowed to attest packets for `ProcessIBCPacket`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelayerSet {
+    pub relayers: Vec<RelayerKey>,
+    /// Distinct relayer signatures required per packet
+    pub threshold: u32,
+}
+
+/// Channel opened with a counterparty order contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChannelInfo {
+    pub channel_id: String,
+    pub counterparty_endpoint: IbcEndpoint,
+    pub connection_id: String,
+}
+
 // Storage - minimal storage
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const ORDERS: Map<String, Order> = Map::new("orders");
-pub const MAKER_ORDERS: Map<Addr, Vec<String>> = Map::new("maker_orders");
\ No newline at end of file
+// Chain registry: chain_id -> route
+pub const ROUTES: IndexedMap<String, Route, RouteIndexes> = IndexedMap::new(
+    "routes",
+    RouteIndexes {
+        channel: UniqueIndex::new(|r| r.channel_id.clone(), "routes__channel"),
+        source: UniqueIndex::new(route_source, "routes__source"),
+    },
+);
+pub const MAKER_ORDERS: Map<Addr, Vec<String>> = Map::new("maker_orders");
+// Connected channels: channel_id -> info
+pub const CHANNELS: Map<String, ChannelInfo> = Map::new("channels");
+// Last sequence assigned to an outgoing packet: channel_id -> sequence
+pub const SEND_SEQUENCES: Map<String, u64> = Map::new("send_sequences");
+// Sequences already accepted from the counterparty: (channel_id, sequence)
+pub const RECEIVED_SEQUENCES: Map<(String, u64), bool> = Map::new("received_sequences");
+
+// Interchain accounts: chain_id -> account, and their open channels: channel_id -> chain_id
+pub const INTERCHAIN_ACCOUNTS: Map<String, InterchainAccount> = Map::new("interchain_accounts");
+pub const ICA_CHANNELS: Map<String, String> = Map::new("ica_channels");
+
+pub const RELAYER_SET: Item<RelayerSet> = Item::new("relayer_set");
+
+// Outgoing packets of relayed routes: (channel_id, sequence) -> packet data
+pub const RELAY_OUTBOX: Map<(String, u64), Binary> = Map::new("relay_outbox");
+
+// Swap hash of the incoming order whose HTLC creation is in flight
+pub const RECEIVING_ORDER: Item<String> = Item::new("receiving_order");
+
+// Swap hash of the order whose held funds are being locked in its HTLC
+pub const FUNDING_ORDER: Item<String> = Item::new("funding_order");
+
+// Swap hash of the order whose received secret is being revealed on its HTLC
+pub const REVEALING_ORDER: Item<String> = Item::new("revealing_order");
@@ -0,0 +1,335 @@
+//! Cross-chain transports behind order dispatch and receipt.
+//!
+//! Every route names a `Transport`. Outgoing packets are the same JSON `IBCPacket` envelope
+//! whatever the transport; only the carrier differs. Inbound messages are authenticated by
+//! their transport (channel, gateway, mailbox or relayer attestations) and then verified like any other packet.
+//! Gateways and mailboxes are plain addresses in the route, so a local mock contract can stand
+//! in for them.
+//!
+//! Axelar and Hyperlane charge a fee per packet. Messages sending a packet carry its fee;
+//! packets the contract sends on its own, answering the counterparty or the factory hook, are
+//! paid from its balance.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, to_json_string, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, HexBinary,
+    IbcMsg, IbcTimeout, MessageInfo, Response, StdError, Storage, WasmMsg,
+};
+use cw_utils::NativeBalance;
+use serde::Serialize;
+
+use crate::error::ContractError;
+use crate::ibc::handle_packet;
+use crate::msg::{Attestation, Transport};
+use crate::proto::encode_msg_transfer;
+use crate::relayer::{receipt_digest, verify_attestations};
+use crate::state::{Route, CHANNELS, RELAYER_SET, RELAY_OUTBOX, ROUTES};
+
+const MSG_TRANSFER: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+
+/// Axelar GMP message type for a payload without tokens.
+const AXELAR_GMP_MESSAGE: u8 = 1;
+
+// ================================================================================================
+// GATEWAY MESSAGES
+// ================================================================================================
+
+/// Memo of the ICS-20 transfer to the Axelar GMP account.
+#[derive(Serialize)]
+struct AxelarMemo<'a> {
+    destination_chain: &'a str,
+    destination_address: &'a str,
+    payload: &'a [u8],
+    #[serde(rename = "type")]
+    message_type: u8,
+    fee: AxelarFee,
+}
+
+#[derive(Serialize)]
+struct AxelarFee {
+    amount: String,
+    recipient: String,
+}
+
+/// Subset of the Hyperlane mailbox interface used to send messages.
+#[cw_serde]
+enum MailboxExecuteMsg {
+    Dispatch(DispatchMsg),
+}
+
+#[cw_serde]
+struct DispatchMsg {
+    dest_domain: u32,
+    recipient_addr: HexBinary,
+    msg_body: HexBinary,
+    hook: Option<String>,
+    metadata: Option<HexBinary>,
+}
+
+// ================================================================================================
+// DISPATCH
+// ================================================================================================
+
+/// Message carrying packet `sequence` to the counterparty of `route`. Relayed packets are
+/// queued for the relayers instead, so there is no message.
+pub fn dispatch(
+    storage: &mut dyn Storage,
+    env: &Env,
+    route: &Route,
+    sequence: u64,
+    data: Binary,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    let timeout = env.block.time.plus_seconds(route.timeout_seconds);
+
+    let msg = match &route.transport {
+        Transport::Ibc => {
+            // Packets can only go out over an open channel
+            if !CHANNELS.has(storage, route.channel_id.clone()) {
+                return Err(ContractError::InvalidIBCChannel { channel: route.channel_id.clone() });
+            }
+
+            IbcMsg::SendPacket {
+                channel_id: route.channel_id.clone(),
+                data,
+                timeout: IbcTimeout::with_timestamp(timeout),
+            }
+            .into()
+        }
+        Transport::Axelar { channel_id, gateway, destination_chain, fee, fee_recipient, .. } => {
+            let memo = AxelarMemo {
+                destination_chain,
+                destination_address: &route.remote_contract,
+                payload: data.as_slice(),
+                message_type: AXELAR_GMP_MESSAGE,
+                fee: AxelarFee {
+                    amount: fee.amount.to_string(),
+                    recipient: fee_recipient.clone(),
+                },
+            };
+
+            // The fee was attached by the sender, or the contract pays its own packets
+            CosmosMsg::Stargate {
+                type_url: MSG_TRANSFER.to_string(),
+                value: encode_msg_transfer(
+                    channel_id,
+                    fee,
+                    env.contract.address.as_str(),
+                    gateway,
+                    timeout.nanos(),
+                    &to_json_string(&memo)?,
+                )
+                .into(),
+            }
+        }
+        Transport::Hyperlane { mailbox, domain, fee } => {
+            let msg = MailboxExecuteMsg::Dispatch(DispatchMsg {
+                dest_domain: *domain,
+                recipient_addr: hyperlane_address(&route.remote_contract)?,
+                msg_body: data.to_vec().into(),
+                hook: None,
+                metadata: None,
+            });
+
+            WasmMsg::Execute {
+                contract_addr: mailbox.clone(),
+                msg: to_json_binary(&msg)?,
+                funds: fee.iter().cloned().collect(),
+            }
+            .into()
+        }
+        Transport::Relayed => {
+            RELAY_OUTBOX.save(storage, (route.channel_id.clone(), sequence), &data)?;
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(msg))
+}
+
+/// Fee of one packet over `route`, if its transport charges any.
+pub fn packet_fee(route: &Route) -> Option<Coin> {
+    match &route.transport {
+        Transport::Axelar { fee, .. } => Some(fee.clone()),
+        Transport::Hyperlane { fee, .. } => fee.clone(),
+        Transport::Ibc | Transport::Relayed => None,
+    }
+    .filter(|fee| !fee.amount.is_zero())
+}
+
+/// Take the fee of a packet over `route` out of `funds`, returning what is left.
+pub fn take_packet_fee(route: &Route, funds: &[Coin]) -> Result<Vec<Coin>, ContractError> {
+    let mut balance = NativeBalance(funds.to_vec());
+    balance.normalize();
+
+    let Some(fee) = packet_fee(route) else {
+        return Ok(balance.into_vec());
+    };
+    let rest = (balance - fee.clone()).map_err(|_| ContractError::InsufficientFee { fee: fee.to_string() })?;
+    Ok(rest.into_vec())
+}
+
+/// Drop a relayed packet from the outbox once the relayer set attests its delivery.
+pub fn acknowledge_relay_packet(
+    deps: DepsMut,
+    env: Env,
+    channel_id: String,
+    sequence: u64,
+    attestations: Vec<Attestation>,
+) -> Result<Response, ContractError> {
+    let key = (channel_id.clone(), sequence);
+    if !RELAY_OUTBOX.has(deps.storage, key.clone()) {
+        return Err(ContractError::RelayPacketNotFound { channel: channel_id, sequence });
+    }
+
+    let set = RELAYER_SET
+        .may_load(deps.storage)?
+        .filter(|set| !set.relayers.is_empty())
+        .ok_or(ContractError::NoRelayerSet)?;
+    let digest = receipt_digest(&env, &channel_id, sequence);
+    verify_attestations(deps.api, &set, &digest, &attestations)?;
+
+    RELAY_OUTBOX.remove(deps.storage, key);
+
+    Ok(Response::new()
+        .add_attribute("method", "acknowledge_relay_packet")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("sequence", sequence.to_string()))
+}
+
+// ================================================================================================
+// RECEIPT
+// ================================================================================================
+
+/// Axelar GMP message; only the route's ibc-hooks sender can deliver it.
+pub fn receive_gmp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    source_chain: String,
+    source_address: String,
+    payload: Binary,
+) -> Result<Response, ContractError> {
+    let (_, route) = ROUTES
+        .idx
+        .source
+        .item(deps.storage, axelar_source(&source_chain, &source_address))?
+        .ok_or_else(|| ContractError::InvalidPacketSource {
+            msg: format!("{} on {}", source_address, source_chain),
+        })?;
+
+    let Transport::Axelar { inbound_sender, .. } = &route.transport else {
+        return Err(ContractError::InvalidPacketSource { msg: source_address });
+    };
+    if info.sender != *inbound_sender {
+        return Err(ContractError::Unauthorized {
+            msg: "Only the Axelar channel can deliver GMP messages".to_string(),
+        });
+    }
+
+    let res = handle_packet(deps, env, &route.channel_id, &payload)?;
+    Ok(res.add_attribute("transport", "axelar"))
+}
+
+/// Hyperlane message; only the route's mailbox can deliver it.
+pub fn receive_hyperlane(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    origin: u32,
+    sender: HexBinary,
+    body: HexBinary,
+) -> Result<Response, ContractError> {
+    // The mailbox is part of the source, so only the route's own mailbox finds it
+    let (_, route) = ROUTES
+        .idx
+        .source
+        .item(deps.storage, hyperlane_source(info.sender.as_str(), origin, &sender))?
+        .ok_or_else(|| ContractError::InvalidPacketSource {
+            msg: format!("{} on domain {}", sender, origin),
+        })?;
+
+    let res = handle_packet(deps, env, &route.channel_id, &body)?;
+    Ok(res.add_attribute("transport", "hyperlane"))
+}
+
+/// Key of the route's inbound source in the route index: the transport and what identifies
+/// a sender on it.
+pub fn route_source(route: &Route) -> (String, String) {
+    match &route.transport {
+        Transport::Ibc => ("ibc".to_string(), route.channel_id.clone()),
+        Transport::Axelar { destination_chain, .. } => {
+            axelar_source(destination_chain, &route.remote_contract)
+        }
+        Transport::Hyperlane { mailbox, domain, .. } => {
+            // Validated when the route is saved
+            let sender = hyperlane_address(&route.remote_contract).unwrap_or_default();
+            hyperlane_source(mailbox, *domain, &sender)
+        }
+        Transport::Relayed => ("relayed".to_string(), route.channel_id.clone()),
+    }
+}
+
+/// Axelar addresses are compared case-insensitively, as EVM addresses come checksummed.
+fn axelar_source(source_chain: &str, source_address: &str) -> (String, String) {
+    ("axelar".to_string(), format!("{}/{}", source_chain, source_address.to_lowercase()))
+}
+
+fn hyperlane_source(mailbox: &str, origin: u32, sender: &HexBinary) -> (String, String) {
+    ("hyperlane".to_string(), format!("{}/{}/{}", mailbox, origin, sender.to_hex()))
+}
+
+// ================================================================================================
+// HELPERS
+// ================================================================================================
+
+/// Transport settings must be complete before a route is saved.
+pub fn validate_transport(deps: Deps, route: &Route) -> Result<(), ContractError> {
+    match &route.transport {
+        Transport::Ibc => {}
+        Transport::Axelar { channel_id, gateway, destination_chain, fee, fee_recipient, inbound_sender } => {
+            if channel_id.is_empty() || gateway.is_empty() || destination_chain.is_empty() {
+                return Err(ContractError::InvalidRoute {
+                    msg: "Axelar channel_id, gateway and destination_chain are required".to_string(),
+                });
+            }
+            if fee.amount.is_zero() || fee_recipient.is_empty() {
+                return Err(ContractError::InvalidRoute {
+                    msg: "Axelar messages need a fee and fee recipient".to_string(),
+                });
+            }
+            deps.api.addr_validate(inbound_sender)?;
+        }
+        Transport::Hyperlane { mailbox, .. } => {
+            deps.api.addr_validate(mailbox)?;
+            hyperlane_address(&route.remote_contract)?;
+        }
+        Transport::Relayed => {}
+    }
+
+    Ok(())
+}
+
+/// Hyperlane's 32-byte form of an account: EVM `0x` addresses and bech32 addresses are
+/// left-padded with zeros.
+pub fn hyperlane_address(address: &str) -> Result<HexBinary, ContractError> {
+    let invalid = |msg: String| ContractError::InvalidAddress {
+        address: address.to_string(),
+        msg,
+    };
+
+    let bytes = match address.strip_prefix("0x") {
+        Some(hex) => HexBinary::from_hex(hex).map_err(|e: StdError| invalid(e.to_string()))?.to_vec(),
+        None => {
+            use bech32::FromBase32;
+            let (_, data, _) = bech32::decode(address).map_err(|e| invalid(e.to_string()))?;
+            Vec::<u8>::from_base32(&data).map_err(|e| invalid(e.to_string()))?
+        }
+    };
+    if bytes.len() > 32 {
+        return Err(invalid("longer than 32 bytes".to_string()));
+    }
+
+    let mut padded = vec![0u8; 32 - bytes.len()];
+    padded.extend(bytes);
+    Ok(padded.into())
+}
@@ -0,0 +1,89 @@
+//! Packets received over native IBC channels, driven through the contract's entry points.
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, IbcChannel, IbcChannelConnectMsg, IbcEndpoint, IbcPacket,
+    IbcPacketReceiveMsg, IbcTimeout, OwnedDeps, Timestamp,
+};
+use ibc::contract::{execute, instantiate};
+use ibc::ibc::{
+    ibc_channel_connect, ibc_packet_receive, AckError, IBCAck, IBCAction, IBCPacket, IBC_ORDERING,
+    IBC_VERSION,
+};
+use ibc::msg::{ExecuteMsg, InstantiateMsg, OrderStatus, Transport};
+
+type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+const CHANNEL: &str = "channel-0";
+const REMOTE_CHAIN: &str = "juno-1";
+const REMOTE_CONTRACT: &str = "juno1orders";
+
+/// Order contract with an IBC route to `REMOTE_CONTRACT`, over a channel to `counterparty_port`.
+fn setup(counterparty_port: &str) -> Deps {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg { admin: "admin".to_string(), htlc_factory: "factory".to_string() };
+    instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::SetRoute {
+        chain_id: REMOTE_CHAIN.to_string(),
+        channel_id: CHANNEL.to_string(),
+        transport: Transport::Ibc,
+        remote_contract: REMOTE_CONTRACT.to_string(),
+        remote_factory: "juno1factory".to_string(),
+        address_prefix: "juno".to_string(),
+        timeout_seconds: 600,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    let channel = IbcChannel::new(
+        IbcEndpoint { port_id: format!("wasm.{}", mock_env().contract.address), channel_id: CHANNEL.to_string() },
+        IbcEndpoint { port_id: counterparty_port.to_string(), channel_id: "channel-7".to_string() },
+        IBC_ORDERING,
+        IBC_VERSION,
+        "connection-0",
+    );
+    ibc_channel_connect(deps.as_mut(), mock_env(), IbcChannelConnectMsg::new_ack(channel, IBC_VERSION)).unwrap();
+    deps
+}
+
+/// Status update for an unknown order from the route's contract, as the ack it gets.
+fn receive_status_update(deps: &mut Deps) -> IBCAck {
+    let packet = IBCPacket {
+        source_chain: REMOTE_CHAIN.to_string(),
+        source_contract: REMOTE_CONTRACT.to_string(),
+        sequence: 1,
+        action: IBCAction::UpdateOrderStatus { swap_hash: "swap-1".to_string(), status: OrderStatus::Funded },
+    };
+    let packet = IbcPacket::new(
+        to_json_binary(&packet).unwrap(),
+        IbcEndpoint { port_id: "wasm.juno1orders".to_string(), channel_id: "channel-7".to_string() },
+        IbcEndpoint { port_id: format!("wasm.{}", mock_env().contract.address), channel_id: CHANNEL.to_string() },
+        1,
+        IbcTimeout::with_timestamp(Timestamp::from_seconds(u64::MAX / 1_000_000_000)),
+    );
+    let msg = IbcPacketReceiveMsg::new(packet, Addr::unchecked("relayer"));
+    let res = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+    from_json(res.acknowledgement).unwrap()
+}
+
+fn ack_error(ack: IBCAck) -> String {
+    match ack {
+        IBCAck::Error(AckError::Other { msg }) => msg,
+        _ => panic!("expected an error acknowledgement"),
+    }
+}
+
+#[test]
+fn packets_must_come_from_the_channel_counterparty() {
+    // The channel was opened by another contract than the one the route names
+    let mut deps = setup("wasm.juno1intruder");
+    let error = ack_error(receive_status_update(&mut deps));
+    assert!(error.starts_with("Invalid packet source"), "{error}");
+}
+
+#[test]
+fn packets_from_the_channel_counterparty_are_applied() {
+    let mut deps = setup("wasm.juno1orders");
+    let error = ack_error(receive_status_update(&mut deps));
+    assert_eq!(error, "Order not found: swap-1");
+}
@@ -0,0 +1,218 @@
+//! Mock Axelar GMP gateway.
+//!
+//! Outbound GMP messages are ICS-20 transfers to the Axelar GMP account with the message in
+//! the memo, so the mock sits in the app's stargate module: it decodes each `MsgTransfer`,
+//! moves the fee to the gateway account and records the message. Inbound messages are
+//! delivered by the Axelar channel's ibc-hooks sender executing `ReceiveGmp`.
+
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Result as AnyResult};
+use cosmwasm_std::{
+    Addr, Api, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, CustomQuery, Storage, Uint128,
+};
+use cw_multi_test::{AppResponse, CosmosRouter, Executor, Stargate};
+use ibc::msg::ExecuteMsg;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::TestApp;
+
+const MSG_TRANSFER: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+
+/// GMP message taken from the memo of a transfer to the gateway.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GmpMessage {
+    pub sender: Addr,
+    pub channel_id: String,
+    pub fee: Coin,
+    pub destination_chain: String,
+    pub destination_address: String,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct Memo {
+    destination_chain: String,
+    destination_address: String,
+    payload: Vec<u8>,
+    #[serde(rename = "type")]
+    message_type: u8,
+}
+
+#[derive(Clone)]
+pub struct MockAxelarGateway {
+    pub gateway: Addr,
+    pub inbound_sender: Addr,
+    sent: Rc<RefCell<Vec<GmpMessage>>>,
+}
+
+impl MockAxelarGateway {
+    pub fn new(gateway: Addr, inbound_sender: Addr) -> Self {
+        Self { gateway, inbound_sender, sent: Rc::default() }
+    }
+
+    /// Messages sent through the gateway so far.
+    pub fn sent(&self) -> Vec<GmpMessage> {
+        self.sent.borrow().clone()
+    }
+
+    /// Deliver a message from `source_address` on `source_chain` to `contract`.
+    pub fn deliver(
+        &self,
+        app: &mut TestApp,
+        contract: &Addr,
+        source_chain: &str,
+        source_address: &str,
+        payload: Vec<u8>,
+    ) -> AnyResult<AppResponse> {
+        self.deliver_from(app, &self.inbound_sender.clone(), contract, source_chain, source_address, payload)
+    }
+
+    /// Like `deliver`, but executed by `sender` instead of the Axelar channel.
+    pub fn deliver_from(
+        &self,
+        app: &mut TestApp,
+        sender: &Addr,
+        contract: &Addr,
+        source_chain: &str,
+        source_address: &str,
+        payload: Vec<u8>,
+    ) -> AnyResult<AppResponse> {
+        let msg = ExecuteMsg::ReceiveGmp {
+            source_chain: source_chain.to_string(),
+            source_address: source_address.to_string(),
+            payload: Binary::from(payload),
+        };
+        app.execute_contract(sender.clone(), contract.clone(), &msg, &[])
+    }
+}
+
+impl Stargate for MockAxelarGateway {
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        type_url: String,
+        value: Binary,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        if type_url != MSG_TRANSFER {
+            bail!("Unexpected stargate message: {}", type_url);
+        }
+
+        let transfer = Transfer::decode(value.as_slice())?;
+        if transfer.receiver != self.gateway.as_str() {
+            bail!("Transfer to {} instead of the Axelar gateway", transfer.receiver);
+        }
+        let memo: Memo = serde_json::from_str(&transfer.memo)?;
+        if memo.message_type != 1 {
+            bail!("Unexpected GMP message type {}", memo.message_type);
+        }
+
+        // The transferred coin pays for the message
+        router.execute(
+            api,
+            storage,
+            block,
+            sender.clone(),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: self.gateway.to_string(),
+                amount: vec![transfer.token.clone()],
+            }),
+        )?;
+
+        self.sent.borrow_mut().push(GmpMessage {
+            sender,
+            channel_id: transfer.source_channel,
+            fee: transfer.token,
+            destination_chain: memo.destination_chain,
+            destination_address: memo.destination_address,
+            payload: memo.payload,
+        });
+        Ok(AppResponse::default())
+    }
+}
+
+// ================================================================================================
+// PROTOBUF
+// ================================================================================================
+
+/// Fields of `ibc.applications.transfer.v1.MsgTransfer` the gateway looks at.
+#[derive(Default)]
+struct Transfer {
+    source_channel: String,
+    token: Coin,
+    receiver: String,
+    memo: String,
+}
+
+impl Transfer {
+    fn decode(buf: &[u8]) -> AnyResult<Self> {
+        let mut transfer = Transfer::default();
+        for (field, value) in fields(buf)? {
+            match field {
+                2 => transfer.source_channel = String::from_utf8(value)?,
+                3 => {
+                    for (field, value) in fields(&value)? {
+                        match field {
+                            1 => transfer.token.denom = String::from_utf8(value)?,
+                            2 => transfer.token.amount = String::from_utf8(value)?.parse::<Uint128>()?,
+                            _ => {}
+                        }
+                    }
+                }
+                5 => transfer.receiver = String::from_utf8(value)?,
+                8 => transfer.memo = String::from_utf8(value)?,
+                _ => {}
+            }
+        }
+        Ok(transfer)
+    }
+}
+
+/// Field numbers and payloads of a proto3 message; varints are returned as empty payloads.
+fn fields(mut buf: &[u8]) -> AnyResult<Vec<(u64, Vec<u8>)>> {
+    let mut fields = vec![];
+    while !buf.is_empty() {
+        let key = varint(&mut buf)?;
+        match key & 7 {
+            0 => {
+                varint(&mut buf)?;
+                fields.push((key >> 3, vec![]));
+            }
+            2 => {
+                let len = varint(&mut buf)? as usize;
+                if buf.len() < len {
+                    bail!("Truncated field {}", key >> 3);
+                }
+                fields.push((key >> 3, buf[..len].to_vec()));
+                buf = &buf[len..];
+            }
+            wire_type => bail!("Unsupported wire type {}", wire_type),
+        }
+    }
+    Ok(fields)
+}
+
+fn varint(buf: &mut &[u8]) -> AnyResult<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first().ok_or_else(|| anyhow!("Truncated varint"))?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Varint too long")
+}
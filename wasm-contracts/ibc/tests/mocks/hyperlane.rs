@@ -0,0 +1,88 @@
+//! Mock Hyperlane mailbox contract.
+//!
+//! `Dispatch` records outbound messages instead of sending them; `Process` delivers an inbound
+//! message by calling the recipient's `handle`, as the mailbox does once a message is verified.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Empty, Env, HexBinary, MessageInfo, Response,
+    StdResult, WasmMsg,
+};
+use cw_multi_test::{Contract, ContractWrapper};
+use cw_storage_plus::Item;
+use ibc::msg::ExecuteMsg as RecipientExecuteMsg;
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    Dispatch(DispatchMsg),
+    Process {
+        origin: u32,
+        sender: HexBinary,
+        recipient: String,
+        body: HexBinary,
+    },
+}
+
+#[cw_serde]
+pub struct DispatchMsg {
+    pub dest_domain: u32,
+    pub recipient_addr: HexBinary,
+    pub msg_body: HexBinary,
+    pub hook: Option<String>,
+    pub metadata: Option<HexBinary>,
+}
+
+#[cw_serde]
+pub enum QueryMsg {
+    Dispatched {},
+}
+
+#[cw_serde]
+pub struct DispatchedMessage {
+    pub sender: String,
+    pub dest_domain: u32,
+    pub recipient_addr: HexBinary,
+    pub msg_body: HexBinary,
+}
+
+const DISPATCHED: Item<Vec<DispatchedMessage>> = Item::new("dispatched");
+
+fn instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+    DISPATCHED.save(deps.storage, &vec![])?;
+    Ok(Response::new())
+}
+
+fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Dispatch(msg) => {
+            DISPATCHED.update(deps.storage, |mut dispatched| -> StdResult<_> {
+                dispatched.push(DispatchedMessage {
+                    sender: info.sender.to_string(),
+                    dest_domain: msg.dest_domain,
+                    recipient_addr: msg.recipient_addr,
+                    msg_body: msg.msg_body,
+                });
+                Ok(dispatched)
+            })?;
+            Ok(Response::new())
+        }
+        ExecuteMsg::Process { origin, sender, recipient, body } => {
+            let handle = RecipientExecuteMsg::Handle { origin, sender, body };
+            Ok(Response::new().add_message(WasmMsg::Execute {
+                contract_addr: recipient,
+                msg: to_json_binary(&handle)?,
+                funds: vec![],
+            }))
+        }
+    }
+}
+
+fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Dispatched {} => to_json_binary(&DISPATCHED.load(deps.storage)?),
+    }
+}
+
+pub fn contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
@@ -0,0 +1,277 @@
+//! Test app with the order contract, the escrow factory and mock transports.
+
+// Each test crate uses a different part of the suite
+#![allow(dead_code)]
+
+pub mod axelar;
+pub mod hyperlane;
+
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{coin, from_json, Addr, Coin, Empty, HexBinary, Uint128};
+use cw_multi_test::addons::{MockAddressGenerator, MockApiBech32};
+use cw_multi_test::{
+    App, AppBuilder, AppResponse, BankKeeper, ContractWrapper, DistributionKeeper, Executor,
+    FailingModule, GovFailingModule, IbcFailingModule, StakeKeeper, WasmKeeper,
+};
+use cw_utils::NativeBalance;
+use htlc_msgs::factory::{HTLCInfo, InstantiateMsg as FactoryInstantiateMsg, QueryMsg as FactoryQueryMsg};
+use ibc::ibc::{IBCAction, IBCPacket, OrderPacket};
+use ibc::msg::{ExecuteMsg, InstantiateMsg, OrderInfo, QueryMsg, Timelocks, Transport};
+use ibc::transport::hyperlane_address;
+use sha2::{Digest, Sha256};
+
+use self::axelar::MockAxelarGateway;
+
+pub type TestApp = App<
+    BankKeeper,
+    MockApiBech32,
+    MockStorage,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    MockAxelarGateway,
+>;
+
+pub const DENOM: &str = "uosmo";
+pub const AXELAR_CHAIN: &str = "ethereum-1";
+pub const AXELAR_CHANNEL: &str = "axelar-ethereum";
+pub const AXELAR_CHAIN_NAME: &str = "Ethereum";
+pub const HYPERLANE_CHAIN: &str = "arbitrum-1";
+pub const HYPERLANE_CHANNEL: &str = "hyperlane-arbitrum";
+pub const HYPERLANE_DOMAIN: u32 = 42161;
+/// Order contracts on the counterparty chains
+pub const AXELAR_REMOTE: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+pub const HYPERLANE_REMOTE: &str = "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512";
+
+pub struct Suite {
+    pub app: TestApp,
+    pub admin: Addr,
+    pub maker: Addr,
+    pub resolver: Addr,
+    pub contract: Addr,
+    pub factory: Addr,
+    pub gateway: MockAxelarGateway,
+    pub mailbox: Addr,
+}
+
+impl Suite {
+    /// Order contract with an Axelar and a Hyperlane route, and fees for the Axelar one.
+    pub fn new() -> Self {
+        let api = MockApiBech32::new("osmo");
+        let admin = api.addr_make("admin");
+        let maker = api.addr_make("maker");
+        let resolver = api.addr_make("resolver");
+        let gateway = MockAxelarGateway::new(api.addr_make("axelar-gmp"), api.addr_make("axelar-hooks"));
+
+        let mut app = AppBuilder::new()
+            .with_api(api)
+            .with_wasm(WasmKeeper::new().with_address_generator(MockAddressGenerator))
+            .with_stargate(gateway.clone())
+            .build(|router, _, storage| {
+                router.bank.init_balance(storage, &maker, vec![coin(1_000_000, DENOM)]).unwrap();
+            });
+
+        let escrow_code = app.store_code(Box::new(
+            ContractWrapper::new(escrow::contract::execute, escrow::contract::instantiate, escrow::contract::query)
+                .with_reply(escrow::contract::reply),
+        ));
+        let factory_code = app.store_code(Box::new(
+            ContractWrapper::new(
+                escrow_factory::contract::execute,
+                escrow_factory::contract::instantiate,
+                escrow_factory::contract::query,
+            )
+            .with_reply(escrow_factory::contract::reply),
+        ));
+        let contract_code = app.store_code(Box::new(
+            ContractWrapper::new(ibc::contract::execute, ibc::contract::instantiate, ibc::contract::query)
+                .with_reply(ibc::contract::reply),
+        ));
+        let mailbox_code = app.store_code(hyperlane::contract());
+
+        let factory = app
+            .instantiate_contract(
+                factory_code,
+                admin.clone(),
+                &FactoryInstantiateMsg { admin: admin.to_string(), htlc_code_id: escrow_code },
+                &[],
+                "factory",
+                None,
+            )
+            .unwrap();
+        let contract = app
+            .instantiate_contract(
+                contract_code,
+                admin.clone(),
+                &InstantiateMsg { admin: admin.to_string(), htlc_factory: factory.to_string() },
+                &[],
+                "orders",
+                None,
+            )
+            .unwrap();
+        let mailbox = app
+            .instantiate_contract(mailbox_code, admin.clone(), &Empty {}, &[], "mailbox", None)
+            .unwrap();
+
+        // Packets the contract sends on its own pay their Axelar fee from its balance
+        app.send_tokens(maker.clone(), contract.clone(), &[coin(1_000, DENOM)]).unwrap();
+
+        let mut suite = Suite { app, admin, maker, resolver, contract, factory, gateway, mailbox };
+        suite.set_route(AXELAR_CHAIN, AXELAR_CHANNEL, AXELAR_REMOTE, suite.axelar_transport()).unwrap();
+        suite.set_route(HYPERLANE_CHAIN, HYPERLANE_CHANNEL, HYPERLANE_REMOTE, suite.hyperlane_transport()).unwrap();
+        suite
+    }
+
+    pub fn axelar_transport(&self) -> Transport {
+        Transport::Axelar {
+            channel_id: "channel-3".to_string(),
+            gateway: self.gateway.gateway.to_string(),
+            destination_chain: AXELAR_CHAIN_NAME.to_string(),
+            fee: self.axelar_fee(),
+            fee_recipient: "axelar1feerecipient".to_string(),
+            inbound_sender: self.gateway.inbound_sender.to_string(),
+        }
+    }
+
+    pub fn axelar_fee(&self) -> Coin {
+        coin(100, DENOM)
+    }
+
+    pub fn hyperlane_transport(&self) -> Transport {
+        Transport::Hyperlane {
+            mailbox: self.mailbox.to_string(),
+            domain: HYPERLANE_DOMAIN,
+            fee: None,
+        }
+    }
+
+    pub fn set_route(
+        &mut self,
+        chain_id: &str,
+        channel_id: &str,
+        remote_contract: &str,
+        transport: Transport,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::SetRoute {
+            chain_id: chain_id.to_string(),
+            channel_id: channel_id.to_string(),
+            transport,
+            remote_contract: remote_contract.to_string(),
+            remote_factory: "0x9fE46736679d2D9a65F0992F2272dE9f3c7fa6e0".to_string(),
+            address_prefix: "osmo".to_string(),
+            timeout_seconds: 600,
+        };
+        self.app.execute_contract(self.admin.clone(), self.contract.clone(), &msg, &[])
+    }
+
+    pub fn timelocks(&self) -> Timelocks {
+        let now = self.app.block_info().time.seconds();
+        Timelocks {
+            withdrawal: now + 60,
+            public_withdrawal: now + 120,
+            cancellation: now + 3_600,
+            public_cancellation: now + 7_200,
+        }
+    }
+
+    /// Destination schedule that expires before `timelocks`.
+    pub fn dst_timelocks(&self) -> Timelocks {
+        let now = self.app.block_info().time.seconds();
+        Timelocks {
+            withdrawal: now + 60,
+            public_withdrawal: now + 120,
+            cancellation: now + 1_800,
+            public_cancellation: now + 3_600,
+        }
+    }
+
+    /// Order parameters with the hashlock of `secret`.
+    pub fn order(&self, swap_hash: &str, secret: &[u8]) -> OrderPacket {
+        OrderPacket {
+            swap_hash: swap_hash.to_string(),
+            maker: self.maker.to_string(),
+            amount: Uint128::new(1_000),
+            denom: DENOM.to_string(),
+            hashlock: Sha256::digest(secret).to_vec(),
+            timelocks: self.timelocks(),
+            dst_timelocks: self.dst_timelocks(),
+        }
+    }
+
+    pub fn create_order(&mut self, order: OrderPacket, target_chain: &str) -> anyhow::Result<AppResponse> {
+        self.create_funded_order(order, target_chain, &[])
+    }
+
+    /// Order whose maker attaches `funds` to be held until the counterparty confirms, on top of
+    /// the transport fee.
+    pub fn create_funded_order(
+        &mut self,
+        order: OrderPacket,
+        target_chain: &str,
+        funds: &[Coin],
+    ) -> anyhow::Result<AppResponse> {
+        let mut funds = NativeBalance(funds.to_vec());
+        if target_chain == AXELAR_CHAIN {
+            funds += self.axelar_fee();
+        }
+        self.create_order_with(order, target_chain, &funds.into_vec())
+    }
+
+    /// Order sent by the maker with exactly `funds` attached.
+    pub fn create_order_with(
+        &mut self,
+        order: OrderPacket,
+        target_chain: &str,
+        funds: &[Coin],
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::CreateOrder {
+            swap_hash: order.swap_hash,
+            maker: order.maker,
+            resolver: self.resolver.to_string(),
+            amount: order.amount,
+            denom: order.denom,
+            hashlock: order.hashlock,
+            timelocks: order.timelocks,
+            dst_timelocks: order.dst_timelocks,
+            target_chain: target_chain.to_string(),
+        };
+        self.app.execute_contract(self.maker.clone(), self.contract.clone(), &msg, funds)
+    }
+
+    pub fn query_order(&self, swap_hash: &str) -> OrderInfo {
+        let msg = QueryMsg::GetOrder { swap_hash: swap_hash.to_string() };
+        self.app.wrap().query_wasm_smart(&self.contract, &msg).unwrap()
+    }
+
+    pub fn query_htlc(&self, swap_hash: &str) -> HTLCInfo {
+        let msg = FactoryQueryMsg::GetHTLC { swap_hash: swap_hash.to_string() };
+        self.app.wrap().query_wasm_smart(&self.factory, &msg).unwrap()
+    }
+
+    pub fn dispatched(&self) -> Vec<hyperlane::DispatchedMessage> {
+        self.app.wrap().query_wasm_smart(&self.mailbox, &hyperlane::QueryMsg::Dispatched {}).unwrap()
+    }
+
+    /// Hyperlane form of the counterparty order contract.
+    pub fn hyperlane_remote(&self) -> HexBinary {
+        hyperlane_address(HYPERLANE_REMOTE).unwrap()
+    }
+}
+
+/// Packet as the counterparty order contract on `chain` would send it.
+pub fn packet(chain: &str, remote_contract: &str, sequence: u64, action: IBCAction) -> Vec<u8> {
+    let packet = IBCPacket {
+        source_chain: chain.to_string(),
+        source_contract: remote_contract.to_string(),
+        sequence,
+        action,
+    };
+    serde_json::to_vec(&packet).unwrap()
+}
+
+pub fn parse_packet(data: &[u8]) -> IBCPacket {
+    from_json(data).unwrap()
+}
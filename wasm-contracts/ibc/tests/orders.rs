@@ -0,0 +1,216 @@
+//! Order creation and lifecycle rules, independent of the transport.
+
+mod mocks;
+
+use cosmwasm_std::{coin, Uint128};
+use cw_multi_test::Executor;
+use htlc_msgs::escrow::ExecuteMsg as EscrowExecuteMsg;
+use htlc_msgs::factory::ExecuteMsg as FactoryExecuteMsg;
+use htlc_msgs::types::SwapStatus;
+use ibc::ibc::IBCAction;
+use ibc::msg::{Actor, ExecuteMsg, OrderStatus};
+use ibc::ContractError;
+
+use mocks::{packet, parse_packet, Suite, AXELAR_CHAIN, AXELAR_CHAIN_NAME, AXELAR_REMOTE, DENOM};
+
+fn contract_error(err: anyhow::Error) -> ContractError {
+    err.downcast().unwrap()
+}
+
+// ================================================================================================
+// TIMELOCKS
+// ================================================================================================
+
+#[test]
+fn destination_escrows_must_expire_before_the_source_escrow() {
+    let mut suite = Suite::new();
+
+    // Same schedule on both chains
+    let mut order = suite.order("swap-1", b"secret");
+    order.dst_timelocks = order.timelocks.clone();
+    let err = suite.create_order(order, AXELAR_CHAIN).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidTimelocks { .. }));
+
+    // Destination withdrawals opening after the source ones
+    let mut order = suite.order("swap-1", b"secret");
+    order.dst_timelocks.withdrawal = order.timelocks.withdrawal + 1;
+    order.dst_timelocks.public_withdrawal = order.timelocks.public_withdrawal + 1;
+    let err = suite.create_order(order, AXELAR_CHAIN).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidTimelocks { .. }));
+
+    // Incoming orders are checked as well
+    let mut order = suite.order("swap-2", b"secret");
+    order.dst_timelocks = order.timelocks.clone();
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, IBCAction::CreateHTLC(order));
+    let (contract, gateway) = (suite.contract.clone(), suite.gateway.clone());
+    let err = gateway
+        .deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data)
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidTimelocks { .. }));
+}
+
+#[test]
+fn timelocks_must_cover_the_route_packet_timeout() {
+    let mut suite = Suite::new();
+    let now = suite.app.block_info().time.seconds();
+
+    // The secret needs a packet timeout to come back before the source escrow expires
+    let mut order = suite.order("swap-1", b"secret");
+    order.dst_timelocks.cancellation = order.timelocks.cancellation - 599;
+    order.dst_timelocks.public_cancellation = order.timelocks.cancellation;
+    let err = suite.create_order(order, AXELAR_CHAIN).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidTimelocks { .. }));
+
+    // The destination escrow must still be open when the order can arrive
+    let mut order = suite.order("swap-1", b"secret");
+    order.dst_timelocks.public_withdrawal = now + 60;
+    order.dst_timelocks.cancellation = now + 599;
+    let err = suite.create_order(order, AXELAR_CHAIN).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidTimelocks { .. }));
+
+    let mut order = suite.order("swap-1", b"secret");
+    order.dst_timelocks.cancellation = order.timelocks.cancellation - 600;
+    suite.create_order(order, AXELAR_CHAIN).unwrap();
+}
+
+#[test]
+fn each_chain_creates_its_escrow_on_its_own_schedule() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    suite.create_order(order.clone(), AXELAR_CHAIN).unwrap();
+    assert_eq!(suite.query_htlc("swap-1").timelocks, order.timelocks);
+
+    let incoming = suite.order("swap-2", b"secret");
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, IBCAction::CreateHTLC(incoming.clone()));
+    let (contract, gateway) = (suite.contract.clone(), suite.gateway.clone());
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+    assert_eq!(suite.query_htlc("swap-2").timelocks, incoming.dst_timelocks);
+    assert_eq!(suite.query_order("swap-2").timelocks, incoming.dst_timelocks);
+}
+
+// ================================================================================================
+// FUNDING
+// ================================================================================================
+
+#[test]
+fn confirmations_lock_the_held_funds() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    suite.create_funded_order(order, AXELAR_CHAIN, &[coin(1_000, DENOM)]).unwrap();
+
+    let confirmation = IBCAction::UpdateOrderStatus {
+        swap_hash: "swap-1".to_string(),
+        status: OrderStatus::Created,
+    };
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, confirmation);
+    let (contract, gateway) = (suite.contract.clone(), suite.gateway.clone());
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+
+    let info = suite.query_order("swap-1");
+    assert_eq!(info.status, OrderStatus::Created);
+    assert!(info.locked_funds.is_empty());
+    assert_eq!(suite.query_htlc("swap-1").status, SwapStatus::Funded);
+}
+
+#[test]
+fn factory_hooks_move_orders_with_their_escrow() {
+    let mut suite = Suite::new();
+    let hook = FactoryExecuteMsg::UpdateSecretHook { hook: Some(suite.contract.to_string()) };
+    suite.app.execute_contract(suite.admin.clone(), suite.factory.clone(), &hook, &[]).unwrap();
+
+    let order = suite.order("swap-1", b"secret");
+    suite.create_funded_order(order, AXELAR_CHAIN, &[coin(1_000, DENOM)]).unwrap();
+    let confirmation = IBCAction::UpdateOrderStatus {
+        swap_hash: "swap-1".to_string(),
+        status: OrderStatus::Created,
+    };
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, confirmation);
+    let (contract, gateway) = (suite.contract.clone(), suite.gateway.clone());
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+
+    // Locking the held funds funds the escrow, which the factory reports
+    let info = suite.query_order("swap-1");
+    assert_eq!(info.status, OrderStatus::Funded);
+    let last = info.transitions.last().unwrap();
+    assert_eq!((last.to.clone(), last.actor.clone()), (OrderStatus::Funded, Actor::Factory));
+
+    // Nobody else speaks for the factory
+    let msg = ExecuteMsg::StatusChanged { swap_hash: "swap-1".to_string(), status: SwapStatus::Cancelled };
+    let err = suite.app.execute_contract(suite.maker.clone(), contract, &msg, &[]).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Unauthorized { .. }));
+}
+
+#[test]
+fn late_confirmations_fail_the_order_and_refund_the_maker() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    suite.create_funded_order(order.clone(), AXELAR_CHAIN, &[coin(1_000, DENOM)]).unwrap();
+    let maker_balance = suite.app.wrap().query_balance(&suite.maker, DENOM).unwrap();
+
+    // The counterparty confirms after the source HTLC stopped taking funds
+    suite.app.update_block(|block| {
+        block.time = block.time.plus_seconds(order.timelocks.cancellation - block.time.seconds());
+    });
+    let confirmation = IBCAction::UpdateOrderStatus {
+        swap_hash: "swap-1".to_string(),
+        status: OrderStatus::Created,
+    };
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, confirmation);
+    let (contract, gateway) = (suite.contract.clone(), suite.gateway.clone());
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+
+    let info = suite.query_order("swap-1");
+    assert_eq!(info.status, OrderStatus::Failed);
+    assert!(info.failure_reason.is_some());
+    let refunded = suite.app.wrap().query_balance(&suite.maker, DENOM).unwrap();
+    assert_eq!(refunded.amount, maker_balance.amount + Uint128::new(1_000));
+
+    // The counterparty learns the order failed
+    let sent = suite.gateway.sent();
+    let update = parse_packet(&sent.last().unwrap().payload);
+    assert!(matches!(
+        update.action,
+        IBCAction::UpdateOrderStatus { status: OrderStatus::Failed, .. }
+    ));
+}
+
+// ================================================================================================
+// SECRETS
+// ================================================================================================
+
+#[test]
+fn received_secrets_complete_orders_once_the_escrow_pays_out() {
+    let mut suite = Suite::new();
+    let (contract, gateway) = (suite.contract.clone(), suite.gateway.clone());
+    let secret = vec![7u8; 32];
+    let order = suite.order("swap-1", &secret);
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, IBCAction::CreateHTLC(order.clone()));
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+
+    // A resolver funds the destination escrow for the maker
+    let htlc = suite.query_htlc("swap-1").htlc_address;
+    suite.app.send_tokens(suite.maker.clone(), suite.resolver.clone(), &[coin(1_000, DENOM)]).unwrap();
+    let lock = EscrowExecuteMsg::LockFunds { amount: order.amount, denom: DENOM.to_string() };
+    suite.app.execute_contract(suite.resolver.clone(), htlc, &lock, &[coin(1_000, DENOM)]).unwrap();
+    let maker_balance = suite.app.wrap().query_balance(&suite.maker, DENOM).unwrap().amount;
+
+    // Only the resolver can withdraw yet, so the secret is kept but the order stays open
+    let reveal = || IBCAction::SecretRevealed { swap_hash: "swap-1".to_string(), secret: secret.clone() };
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 2, reveal());
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+    let info = suite.query_order("swap-1");
+    assert_eq!(info.status, OrderStatus::Created);
+    assert_eq!(info.secret, Some(secret.clone()));
+    assert_eq!(suite.query_htlc("swap-1").status, SwapStatus::Funded);
+
+    // In the public withdrawal stage the contract completes the escrow itself
+    suite.app.update_block(|block| {
+        block.time = block.time.plus_seconds(order.dst_timelocks.public_withdrawal - block.time.seconds());
+    });
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 3, reveal());
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+    assert_eq!(suite.query_order("swap-1").status, OrderStatus::Completed);
+    assert_eq!(suite.query_htlc("swap-1").status, SwapStatus::Completed);
+    let paid = suite.app.wrap().query_balance(&suite.maker, DENOM).unwrap().amount;
+    assert_eq!(paid, maker_balance + Uint128::new(1_000));
+}
@@ -0,0 +1,390 @@
+//! Dispatch and receipt of order packets over the Axelar and Hyperlane transports.
+
+mod mocks;
+
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{coin, Binary, HexBinary};
+use cw_multi_test::Executor;
+use ed25519_zebra::{SigningKey, VerificationKey};
+use htlc_msgs::types::{EscrowMode, SwapStatus};
+use ibc::ibc::IBCAction;
+use ibc::msg::{Attestation, ExecuteMsg, KeyType, OrderStatus, QueryMsg, RelayPacket, RelayerKey, Transport};
+use ibc::relayer::receipt_digest;
+use ibc::ContractError;
+
+use mocks::hyperlane::{self, DispatchedMessage};
+use mocks::{
+    packet, parse_packet, Suite, AXELAR_CHAIN, AXELAR_CHAIN_NAME, AXELAR_REMOTE, DENOM,
+    HYPERLANE_CHAIN, HYPERLANE_DOMAIN, HYPERLANE_REMOTE,
+};
+
+fn contract_error(err: anyhow::Error) -> ContractError {
+    err.downcast().unwrap()
+}
+
+// ================================================================================================
+// AXELAR
+// ================================================================================================
+
+#[test]
+fn axelar_dispatches_orders_through_the_gateway() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    suite.create_order(order.clone(), AXELAR_CHAIN).unwrap();
+
+    let sent = suite.gateway.sent();
+    assert_eq!(sent.len(), 1);
+    let msg = &sent[0];
+    assert_eq!(msg.sender, suite.contract);
+    assert_eq!(msg.channel_id, "channel-3");
+    assert_eq!(msg.destination_chain, AXELAR_CHAIN_NAME);
+    assert_eq!(msg.destination_address, AXELAR_REMOTE);
+    assert_eq!(msg.fee, suite.axelar_fee());
+
+    let packet = parse_packet(&msg.payload);
+    assert_eq!(packet.source_chain, suite.app.block_info().chain_id);
+    assert_eq!(packet.source_contract, suite.contract.to_string());
+    assert_eq!(packet.sequence, 1);
+    let IBCAction::CreateHTLC(remote_order) = packet.action else {
+        panic!("expected a CreateHTLC packet");
+    };
+    assert_eq!(remote_order, order);
+
+    // The maker paid the fee, not the contract
+    let gateway_balance = suite.app.wrap().query_balance(&suite.gateway.gateway, DENOM).unwrap();
+    assert_eq!(gateway_balance, suite.axelar_fee());
+    let contract_balance = suite.app.wrap().query_balance(&suite.contract, DENOM).unwrap();
+    assert_eq!(contract_balance.amount.u128(), 1_000);
+
+    // The maker's source escrow waits for the counterparty
+    let htlc = suite.query_htlc("swap-1");
+    assert_eq!(htlc.mode, EscrowMode::Source);
+    assert_eq!(htlc.resolver, Some(suite.resolver.clone()));
+    assert_eq!(suite.query_order("swap-1").status, OrderStatus::Pending);
+}
+
+#[test]
+fn axelar_fees_are_paid_by_the_sender() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+
+    // Orders without the fee can't go out
+    let err = suite.create_order_with(order.clone(), AXELAR_CHAIN, &[]).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InsufficientFee { .. }));
+
+    // The fee doesn't count towards the order funds
+    let err = suite
+        .create_order_with(order.clone(), AXELAR_CHAIN, &[coin(1_000, DENOM)])
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Std(_)));
+    suite.create_order_with(order, AXELAR_CHAIN, &[coin(1_100, DENOM)]).unwrap();
+    assert_eq!(suite.query_order("swap-1").locked_funds, vec![coin(1_000, DENOM)]);
+
+    // Status updates pay for their packet as well
+    let confirmation = IBCAction::UpdateOrderStatus {
+        swap_hash: "swap-1".to_string(),
+        status: OrderStatus::Created,
+    };
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, confirmation);
+    let (contract, gateway) = (suite.contract.clone(), suite.gateway.clone());
+    gateway.deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data).unwrap();
+    let update = ExecuteMsg::UpdateOrderStatus {
+        swap_hash: "swap-1".to_string(),
+        status: OrderStatus::Cancelled,
+    };
+    let err = suite
+        .app
+        .execute_contract(suite.maker.clone(), suite.contract.clone(), &update, &[])
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InsufficientFee { .. }));
+    let fee = suite.axelar_fee();
+    suite.app.execute_contract(suite.maker.clone(), suite.contract.clone(), &update, &[fee]).unwrap();
+    assert_eq!(suite.gateway.sent().len(), 2);
+}
+
+#[test]
+fn only_makers_create_their_orders() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    let msg = ExecuteMsg::CreateOrder {
+        swap_hash: order.swap_hash,
+        maker: order.maker,
+        resolver: suite.resolver.to_string(),
+        amount: order.amount,
+        denom: order.denom,
+        hashlock: order.hashlock,
+        timelocks: order.timelocks,
+        dst_timelocks: order.dst_timelocks,
+        target_chain: HYPERLANE_CHAIN.to_string(),
+    };
+    let err = suite
+        .app
+        .execute_contract(suite.resolver.clone(), suite.contract.clone(), &msg, &[])
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Unauthorized { .. }));
+}
+
+#[test]
+fn axelar_delivers_orders_from_the_inbound_sender() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, IBCAction::CreateHTLC(order));
+
+    // EVM addresses may arrive in any case
+    let contract = suite.contract.clone();
+    let gateway = suite.gateway.clone();
+    gateway
+        .deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, &AXELAR_REMOTE.to_lowercase(), data)
+        .unwrap();
+
+    let order = suite.query_order("swap-1");
+    assert_eq!(order.target_chain, AXELAR_CHAIN);
+    assert_eq!(order.status, OrderStatus::Created);
+    let htlc = suite.query_htlc("swap-1");
+    assert_eq!(htlc.mode, EscrowMode::Destination);
+    assert_eq!(htlc.status, SwapStatus::Pending);
+    assert_eq!(order.htlc_address, Some(htlc.htlc_address));
+
+    // Without acknowledgements, the creation is confirmed with a status packet
+    let sent = suite.gateway.sent();
+    assert_eq!(sent.len(), 1);
+    let confirmation = parse_packet(&sent[0].payload);
+    assert!(matches!(
+        confirmation.action,
+        IBCAction::UpdateOrderStatus { status: OrderStatus::Created, .. }
+    ));
+}
+
+#[test]
+fn axelar_rejects_other_senders_and_sources() {
+    let mut suite = Suite::new();
+    let contract = suite.contract.clone();
+    let gateway = suite.gateway.clone();
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, IBCAction::CreateHTLC(suite.order("swap-1", b"secret")));
+
+    // Only the Axelar channel delivers GMP messages
+    let maker = suite.maker.clone();
+    let err = gateway
+        .deliver_from(&mut suite.app, &maker, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data.clone())
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Unauthorized { .. }));
+
+    // Sources without a route are unknown
+    let err = gateway
+        .deliver(&mut suite.app, &contract, "Polygon", AXELAR_REMOTE, data.clone())
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidPacketSource { .. }));
+    let err = gateway
+        .deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, HYPERLANE_REMOTE, data)
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidPacketSource { .. }));
+}
+
+// ================================================================================================
+// HYPERLANE
+// ================================================================================================
+
+#[test]
+fn hyperlane_dispatches_orders_through_the_mailbox() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    suite.create_order(order.clone(), HYPERLANE_CHAIN).unwrap();
+
+    let dispatched = suite.dispatched();
+    assert_eq!(dispatched.len(), 1);
+    let DispatchedMessage { sender, dest_domain, recipient_addr, msg_body } = &dispatched[0];
+    assert_eq!(sender, suite.contract.as_str());
+    assert_eq!(*dest_domain, HYPERLANE_DOMAIN);
+    assert_eq!(*recipient_addr, suite.hyperlane_remote());
+
+    let packet = parse_packet(msg_body);
+    assert_eq!(packet.source_contract, suite.contract.to_string());
+    assert_eq!(packet.sequence, 1);
+    let IBCAction::CreateHTLC(remote_order) = packet.action else {
+        panic!("expected a CreateHTLC packet");
+    };
+    assert_eq!(remote_order, order);
+
+    // Nothing went through Axelar
+    assert!(suite.gateway.sent().is_empty());
+}
+
+#[test]
+fn hyperlane_delivers_orders_from_the_mailbox() {
+    let mut suite = Suite::new();
+    let order = suite.order("swap-1", b"secret");
+    let data = packet(HYPERLANE_CHAIN, HYPERLANE_REMOTE, 1, IBCAction::CreateHTLC(order));
+
+    let msg = hyperlane::ExecuteMsg::Process {
+        origin: HYPERLANE_DOMAIN,
+        sender: suite.hyperlane_remote(),
+        recipient: suite.contract.to_string(),
+        body: data.into(),
+    };
+    suite.app.execute_contract(suite.maker.clone(), suite.mailbox.clone(), &msg, &[]).unwrap();
+
+    let order = suite.query_order("swap-1");
+    assert_eq!(order.target_chain, HYPERLANE_CHAIN);
+    assert_eq!(order.status, OrderStatus::Created);
+    assert_eq!(suite.query_htlc("swap-1").mode, EscrowMode::Destination);
+
+    // The confirmation goes back through the mailbox
+    let dispatched = suite.dispatched();
+    assert_eq!(dispatched.len(), 1);
+    let confirmation = parse_packet(&dispatched[0].msg_body);
+    assert_eq!(confirmation.sequence, 1);
+    assert!(matches!(
+        confirmation.action,
+        IBCAction::UpdateOrderStatus { status: OrderStatus::Created, .. }
+    ));
+}
+
+#[test]
+fn hyperlane_rejects_messages_outside_the_route() {
+    let mut suite = Suite::new();
+    let data: HexBinary =
+        packet(HYPERLANE_CHAIN, HYPERLANE_REMOTE, 1, IBCAction::CreateHTLC(suite.order("swap-1", b"secret"))).into();
+
+    // Only the route's mailbox delivers messages
+    let handle = ExecuteMsg::Handle {
+        origin: HYPERLANE_DOMAIN,
+        sender: suite.hyperlane_remote(),
+        body: data.clone(),
+    };
+    let err = suite
+        .app
+        .execute_contract(suite.maker.clone(), suite.contract.clone(), &handle, &[])
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidPacketSource { .. }));
+
+    // Other domains and senders have no route
+    for (origin, sender) in [
+        (1, suite.hyperlane_remote()),
+        (HYPERLANE_DOMAIN, HexBinary::from(vec![7u8; 32])),
+    ] {
+        let msg = hyperlane::ExecuteMsg::Process {
+            origin,
+            sender,
+            recipient: suite.contract.to_string(),
+            body: data.clone(),
+        };
+        let err = suite
+            .app
+            .execute_contract(suite.maker.clone(), suite.mailbox.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(contract_error(err), ContractError::InvalidPacketSource { .. }));
+    }
+}
+
+// ================================================================================================
+// ROUTES
+// ================================================================================================
+
+#[test]
+fn routes_are_found_by_their_source() {
+    let mut suite = Suite::new();
+
+    // A source leads to exactly one chain
+    let transport = suite.axelar_transport();
+    suite.set_route("ethereum-2", "axelar-ethereum-2", AXELAR_REMOTE, transport).unwrap_err();
+
+    // A removed route no longer receives
+    let msg = ExecuteMsg::RemoveRoute { chain_id: AXELAR_CHAIN.to_string() };
+    suite.app.execute_contract(suite.admin.clone(), suite.contract.clone(), &msg, &[]).unwrap();
+    let contract = suite.contract.clone();
+    let gateway = suite.gateway.clone();
+    let data = packet(AXELAR_CHAIN, AXELAR_REMOTE, 1, IBCAction::CreateHTLC(suite.order("swap-1", b"secret")));
+    let err = gateway
+        .deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data)
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InvalidPacketSource { .. }));
+
+    // Its source is free for another chain
+    let transport = suite.axelar_transport();
+    suite.set_route("ethereum-2", "axelar-ethereum-2", AXELAR_REMOTE, transport).unwrap();
+    let data = packet("ethereum-2", AXELAR_REMOTE, 1, IBCAction::CreateHTLC(suite.order("swap-2", b"secret")));
+    gateway
+        .deliver(&mut suite.app, &contract, AXELAR_CHAIN_NAME, AXELAR_REMOTE, data)
+        .unwrap();
+    assert_eq!(suite.query_order("swap-2").target_chain, "ethereum-2");
+}
+
+// ================================================================================================
+// RELAYED
+// ================================================================================================
+
+const RELAYED_CHAIN: &str = "juno-1";
+const RELAYED_CHANNEL: &str = "relayed-juno";
+
+/// Route to `RELAYED_CHAIN` relayed by two of three ed25519 relayers.
+fn relayed_suite() -> (Suite, Vec<SigningKey>) {
+    let mut suite = Suite::new();
+    let keys: Vec<SigningKey> = (1..=3u8).map(|i| SigningKey::from([i; 32])).collect();
+    let msg = ExecuteMsg::UpdateRelayerSet {
+        relayers: keys
+            .iter()
+            .map(|key| RelayerKey {
+                key_type: KeyType::Ed25519,
+                public_key: Binary::from(VerificationKey::from(key).as_ref()),
+            })
+            .collect(),
+        threshold: 2,
+    };
+    suite.app.execute_contract(suite.admin.clone(), suite.contract.clone(), &msg, &[]).unwrap();
+    suite.set_route(RELAYED_CHAIN, RELAYED_CHANNEL, "juno1orders", Transport::Relayed).unwrap();
+    (suite, keys)
+}
+
+fn relay_packets(suite: &Suite) -> Vec<RelayPacket> {
+    let msg = QueryMsg::ListRelayPackets {
+        channel_id: RELAYED_CHANNEL.to_string(),
+        start_after: None,
+        limit: None,
+    };
+    suite.app.wrap().query_wasm_smart(&suite.contract, &msg).unwrap()
+}
+
+/// Receipt for packet `sequence` signed by `keys`.
+fn acknowledge(suite: &mut Suite, keys: &[SigningKey], sequence: u64) -> anyhow::Result<()> {
+    let mut env = mock_env();
+    env.block.chain_id = suite.app.block_info().chain_id;
+    env.contract.address = suite.contract.clone();
+    let digest = receipt_digest(&env, RELAYED_CHANNEL, sequence);
+
+    let msg = ExecuteMsg::AcknowledgeRelayPacket {
+        channel_id: RELAYED_CHANNEL.to_string(),
+        sequence,
+        attestations: keys
+            .iter()
+            .map(|key| Attestation {
+                public_key: Binary::from(VerificationKey::from(key).as_ref()),
+                signature: Binary::from(<[u8; 64]>::from(key.sign(&digest)).to_vec()),
+            })
+            .collect(),
+    };
+    suite.app.execute_contract(suite.resolver.clone(), suite.contract.clone(), &msg, &[])?;
+    Ok(())
+}
+
+#[test]
+fn relayed_packets_leave_the_outbox_once_delivered() {
+    let (mut suite, keys) = relayed_suite();
+    let order = suite.order("swap-1", b"secret");
+    suite.create_order(order, RELAYED_CHAIN).unwrap();
+
+    let packets = relay_packets(&suite);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].sequence, 1);
+    assert!(matches!(parse_packet(&packets[0].data).action, IBCAction::CreateHTLC(_)));
+
+    // One relayer can't vouch for the delivery alone
+    let err = acknowledge(&mut suite, &keys[..1], 1).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::InsufficientAttestations { valid: 1, threshold: 2 }));
+    assert_eq!(relay_packets(&suite).len(), 1);
+
+    acknowledge(&mut suite, &keys[1..], 1).unwrap();
+    assert!(relay_packets(&suite).is_empty());
+
+    let err = acknowledge(&mut suite, &keys[1..], 1).unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::RelayPacketNotFound { sequence: 1, .. }));
+}
@@ -0,0 +1,105 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Coin, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+use crate::types::{
+    Asset, EscrowMode, HashAlgorithm, RevealedSecret, SwapStatus, TimelockStage, Timelocks,
+};
+
+/// Length in bytes of every accepted secret preimage, matching EVM `bytes32` secrets.
+pub const SECRET_LENGTH: usize = 32;
+
+/// Length in bytes of the Merkle root used as hashlock for partial fills.
+pub const MERKLE_ROOT_LENGTH: usize = 32;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub admin: String,
+    #[serde(default)]
+    pub mode: EscrowMode,
+    pub maker: String,
+    /// Taker allowed to withdraw from a source escrow. For destination escrows it is the only
+    /// account allowed to fund the escrow, or the funder when funds are attached to the
+    /// instantiation (factory create-and-fund); when unset, whoever funds it becomes the resolver.
+    pub resolver: Option<String>,
+    pub amount: Uint128,
+    pub asset: Asset,
+    pub hashlock: Vec<u8>,
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Number of equal parts the swap can be filled in. When set, `hashlock` is the Merkle
+    /// root of `parts + 1` secret hashes.
+    pub parts: Option<u32>,
+    pub timelocks: Timelocks,
+    /// Incentive posted by the funder (resolver or, for source escrows, maker) alongside the
+    /// swap amount and paid to whoever completes or cancels the escrow. Only supported for
+    /// native assets.
+    pub safety_deposit: Option<Coin>,
+    /// Registry key of the escrow when created through the factory. When set, the
+    /// instantiator is recorded as the factory and notified of every status change.
+    #[serde(default)]
+    pub swap_hash: Option<String>,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    // Lock funds (resolver deposits the swap amount plus the safety deposit; a source escrow
+    // is funded on behalf of its maker, who is refunded on cancellation)
+    LockFunds {
+        amount: Uint128,
+        denom: String,
+    },
+
+    // Lock CW20 funds (funder sends the tokens with a `ReceiveMsg::LockFunds` hook)
+    Receive(Cw20ReceiveMsg),
+    
+    // Reveal secret to complete swap (or fill part of it, with a Merkle proof)
+    RevealSecret {
+        secret: Vec<u8>,
+        merkle_proof: Option<MerkleProof>,
+    },
+    
+    // Cancel swap (once the cancellation stage starts)
+    CancelSwap {},
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    LockFunds {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(SwapInfo)]
+    GetSwapInfo {},
+}
+
+#[cw_serde]
+pub struct SwapInfo {
+    pub mode: EscrowMode,
+    pub maker: Addr,
+    pub resolver: Option<Addr>,
+    pub amount: Uint128,
+    pub asset: Asset,
+    pub safety_deposit: Option<Coin>,
+    pub hashlock: Vec<u8>,
+    pub hash_algorithm: HashAlgorithm,
+    pub parts: Option<u32>,
+    pub filled_amount: Uint128,
+    pub used_secret_indices: Vec<u32>,
+    pub revealed_secrets: Vec<RevealedSecret>,
+    pub timelocks: Timelocks,
+    pub stage: TimelockStage,
+    pub status: SwapStatus,
+    pub created_at: Timestamp,
+    pub funded_at: Option<Timestamp>,
+    pub completed_at: Option<Timestamp>,
+}
+
+/// Proof that the secret at `index` is a leaf of the hashlock Merkle root.
+#[cw_serde]
+pub struct MerkleProof {
+    pub index: u32,
+    pub proof: Vec<Vec<u8>>,
+}
@@ -0,0 +1,247 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary, Coin, Timestamp, Uint128};
+
+use crate::types::{Asset, EscrowMode, HashAlgorithm, RevealedSecret, SwapStatus, Timelocks};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub admin: String,
+    pub htlc_code_id: u64,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    // Create new HTLC contract; when `resolver` is set, only it may fund the escrow
+    CreateHTLC {
+        swap_hash: String,
+        maker: String,
+        #[serde(default)]
+        resolver: Option<String>,
+        amount: Uint128,
+        asset: Asset,
+        hashlock: Vec<u8>,
+        #[serde(default)]
+        hash_algorithm: HashAlgorithm,
+        // Number of partial fills; `hashlock` is then a Merkle root of `parts + 1` secrets
+        parts: Option<u32>,
+        timelocks: Timelocks,
+        safety_deposit: Option<Coin>,
+    },
+    
+    // Create a destination HTLC and fund it in the same transaction with the swap amount and
+    // the safety deposit, if any, as separate coins (the deposit may be in another denom; in
+    // the same denom they arrive as one coin). The sender is the resolver
+    CreateFundedHTLC {
+        swap_hash: String,
+        maker: String,
+        amount: Uint128,
+        asset: Asset,
+        hashlock: Vec<u8>,
+        #[serde(default)]
+        hash_algorithm: HashAlgorithm,
+        parts: Option<u32>,
+        timelocks: Timelocks,
+        safety_deposit: Option<Coin>,
+    },
+
+    // Create and fund a destination HTLC from an ICS-20 transfer with an ibc-hooks memo:
+    //   {"wasm": {"contract": "<factory>", "msg": {"create_h_t_l_c_from_transfer": {...}}}}
+    // The transferred coin (an `ibc/...` denom) is the swap amount. Hooks execute from an
+    // intermediary address, so `resolver` names the account refunded on cancellation.
+    CreateHTLCFromTransfer {
+        swap_hash: String,
+        maker: String,
+        resolver: String,
+        hashlock: Vec<u8>,
+        #[serde(default)]
+        hash_algorithm: HashAlgorithm,
+        parts: Option<u32>,
+        timelocks: Timelocks,
+    },
+
+    // Create a maker-funded source escrow (Cosmos -> EVM swaps); the maker locks funds
+    // afterwards and `resolver` withdraws with the secret
+    CreateSrcEscrow {
+        swap_hash: String,
+        maker: String,
+        resolver: String,
+        amount: Uint128,
+        asset: Asset,
+        hashlock: Vec<u8>,
+        #[serde(default)]
+        hash_algorithm: HashAlgorithm,
+        parts: Option<u32>,
+        timelocks: Timelocks,
+        safety_deposit: Option<Coin>,
+    },
+
+    // Status callback from an escrow created by this factory; only the registered escrow
+    // address may report for its swap_hash
+    UpdateEscrowStatus {
+        swap_hash: String,
+        status: SwapStatus,
+        resolver: Option<String>,
+        filled_amount: Uint128,
+        #[serde(default)]
+        secret: Option<RevealedSecret>,
+    },
+
+    // Admin functions
+    UpdateHTLCCodeId {
+        code_id: u64,
+    },
+
+    // Contract told about every revealed secret and status change with `SecretHookMsg`, or none
+    UpdateSecretHook {
+        hook: Option<String>,
+    },
+    
+    UpdateAdmin {
+        admin: String,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(HTLCInfo)]
+    GetHTLC { swap_hash: String },
+    
+    // List queries page by swap_hash (`start_after` is exclusive) and can be narrowed to a
+    // single lifecycle stage with `status`
+    #[returns(Vec<HTLCInfo>)]
+    ListHTLCs {
+        status: Option<SwapStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Vec<HTLCInfo>)]
+    GetHTLCsByMaker {
+        maker: String,
+        status: Option<SwapStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Vec<HTLCInfo>)]
+    GetHTLCsByResolver {
+        resolver: String,
+        status: Option<SwapStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Vec<HTLCInfo>)]
+    GetHTLCsByStatus {
+        status: SwapStatus,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    // Escrows ordered by the start of their cancellation stage, optionally only those that
+    // become cancellable before `expires_before`
+    #[returns(Vec<HTLCInfo>)]
+    GetHTLCsByExpiry {
+        expires_before: Option<u64>,
+        status: Option<SwapStatus>,
+        start_after: Option<ExpiryCursor>,
+        limit: Option<u32>,
+    },
+    
+    // First secret revealed for a hashlock (the escrow hashlock, or a Merkle leaf's secret hash)
+    #[returns(SecretResponse)]
+    GetSecretByHashlock { hashlock: Vec<u8> },
+
+    #[returns(ConfigResponse)]
+    GetConfig {},
+
+    // Address the factory will instantiate an escrow with these immutables at
+    #[returns(EscrowAddressResponse)]
+    PredictEscrowAddress { immutables: EscrowImmutables },
+}
+
+/// Everything an escrow is created with. The escrow address is derived from all of it, so
+/// nobody can take the address of an escrow with different terms.
+#[cw_serde]
+pub struct EscrowImmutables {
+    pub swap_hash: String,
+    pub mode: EscrowMode,
+    pub maker: String,
+    /// Taker named at creation: the source escrow's resolver, or the funder of an escrow
+    /// created and funded in one go; `None` for destination escrows funded later
+    pub resolver: Option<String>,
+    pub amount: Uint128,
+    pub asset: Asset,
+    pub hashlock: Vec<u8>,
+    pub hash_algorithm: HashAlgorithm,
+    pub parts: Option<u32>,
+    pub timelocks: Timelocks,
+    pub safety_deposit: Option<Coin>,
+}
+
+#[cw_serde]
+pub struct HTLCInfo {
+    pub swap_hash: String,
+    pub htlc_address: Addr,
+    pub mode: EscrowMode,
+    pub maker: Addr,
+    pub resolver: Option<Addr>,
+    pub amount: Uint128,
+    pub asset: Asset,
+    pub hashlock: Vec<u8>,
+    pub hash_algorithm: HashAlgorithm,
+    pub parts: Option<u32>,
+    pub timelocks: Timelocks,
+    pub safety_deposit: Option<Coin>,
+    pub status: SwapStatus,
+    pub filled_amount: Uint128,
+    pub created_at: Timestamp,
+    pub funded_at: Option<Timestamp>,
+    pub completed_at: Option<Timestamp>,
+    pub cancelled_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct SecretResponse {
+    pub swap_hash: String,
+    pub htlc_address: Addr,
+    pub index: u32,
+    pub secret: Vec<u8>,
+    pub revealed_at: Timestamp,
+}
+
+/// Pagination cursor for `GetHTLCsByExpiry`: the last escrow of the previous page.
+#[cw_serde]
+pub struct ExpiryCursor {
+    pub expiry: u64,
+    pub swap_hash: String,
+}
+
+#[cw_serde]
+pub struct EscrowAddressResponse {
+    pub address: Addr,
+    pub salt: Binary,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub admin: Addr,
+    pub htlc_code_id: u64,
+    pub secret_hook: Option<Addr>,
+}
+
+/// Message the factory sends its secret hook when an escrow reveals a secret or changes status.
+/// Hook failures are ignored, so a hook can never block a withdrawal.
+#[cw_serde]
+pub enum SecretHookMsg {
+    SecretRevealed {
+        swap_hash: String,
+        secret: Vec<u8>,
+    },
+    /// Sent after `SecretRevealed` when both come from the same report
+    StatusChanged {
+        swap_hash: String,
+        status: SwapStatus,
+    },
+}
@@ -0,0 +1,142 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Addr, Coin, CosmosMsg, QuerierWrapper, StdError, StdResult, Uint128, WasmMsg,
+};
+
+use crate::escrow::{self, SwapInfo};
+use crate::factory::{self, EscrowAddressResponse, EscrowImmutables, HTLCInfo, SecretResponse};
+use crate::types::{Asset, EscrowMode, HashAlgorithm, Timelocks};
+
+/// Escrow contract address with helpers to build its messages and queries.
+#[cw_serde]
+pub struct EscrowContract(pub Addr);
+
+impl EscrowContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call(&self, msg: escrow::ExecuteMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_json_binary(&msg)?,
+            funds,
+        }
+        .into())
+    }
+
+    pub fn swap_info(&self, querier: &QuerierWrapper) -> StdResult<SwapInfo> {
+        querier.query_wasm_smart(&self.0, &escrow::QueryMsg::GetSwapInfo {})
+    }
+}
+
+/// Escrow factory address with helpers to build its messages and queries.
+#[cw_serde]
+pub struct FactoryContract(pub Addr);
+
+impl FactoryContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call(&self, msg: factory::ExecuteMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_json_binary(&msg)?,
+            funds,
+        }
+        .into())
+    }
+
+    /// `CreateHTLC` for a single-secret, SHA-256 destination escrow without safety deposit.
+    pub fn create_htlc(
+        &self,
+        swap_hash: impl Into<String>,
+        maker: impl Into<String>,
+        amount: Uint128,
+        asset: Asset,
+        hashlock: Vec<u8>,
+        timelocks: Timelocks,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            factory::ExecuteMsg::CreateHTLC {
+                swap_hash: swap_hash.into(),
+                maker: maker.into(),
+                resolver: None,
+                amount,
+                asset,
+                hashlock,
+                hash_algorithm: HashAlgorithm::default(),
+                parts: None,
+                timelocks,
+                safety_deposit: None,
+            },
+            vec![],
+        )
+    }
+
+    /// `CreateSrcEscrow` or `CreateHTLC` for the escrow described by `immutables`, which then
+    /// lands at `predict_escrow_address(immutables)`.
+    pub fn create_escrow(&self, immutables: EscrowImmutables) -> StdResult<CosmosMsg> {
+        let EscrowImmutables {
+            swap_hash,
+            mode,
+            maker,
+            resolver,
+            amount,
+            asset,
+            hashlock,
+            hash_algorithm,
+            parts,
+            timelocks,
+            safety_deposit,
+        } = immutables;
+
+        let msg = match (mode, resolver) {
+            (EscrowMode::Source, Some(resolver)) => factory::ExecuteMsg::CreateSrcEscrow {
+                swap_hash,
+                maker,
+                resolver,
+                amount,
+                asset,
+                hashlock,
+                hash_algorithm,
+                parts,
+                timelocks,
+                safety_deposit,
+            },
+            (EscrowMode::Destination, resolver) => factory::ExecuteMsg::CreateHTLC {
+                swap_hash,
+                maker,
+                resolver,
+                amount,
+                asset,
+                hashlock,
+                hash_algorithm,
+                parts,
+                timelocks,
+                safety_deposit,
+            },
+            (EscrowMode::Source, None) => {
+                return Err(StdError::generic_err("Source escrows need a resolver"));
+            }
+        };
+        self.call(msg, vec![])
+    }
+
+    pub fn htlc(&self, querier: &QuerierWrapper, swap_hash: impl Into<String>) -> StdResult<HTLCInfo> {
+        querier.query_wasm_smart(&self.0, &factory::QueryMsg::GetHTLC { swap_hash: swap_hash.into() })
+    }
+
+    pub fn predict_escrow_address(
+        &self,
+        querier: &QuerierWrapper,
+        immutables: EscrowImmutables,
+    ) -> StdResult<EscrowAddressResponse> {
+        querier.query_wasm_smart(&self.0, &factory::QueryMsg::PredictEscrowAddress { immutables })
+    }
+
+    pub fn secret_by_hashlock(&self, querier: &QuerierWrapper, hashlock: Vec<u8>) -> StdResult<SecretResponse> {
+        querier.query_wasm_smart(&self.0, &factory::QueryMsg::GetSecretByHashlock { hashlock })
+    }
+}
@@ -0,0 +1,11 @@
+//! Message types shared by the escrow, escrow factory and IBC contracts.
+//!
+//! Contracts build each other's messages from these definitions instead of hand-written
+//! JSON, so a renamed field fails to compile rather than failing on chain.
+
+pub mod escrow;
+pub mod factory;
+pub mod helpers;
+pub mod types;
+
+pub use helpers::{EscrowContract, FactoryContract};
@@ -0,0 +1,115 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Timestamp;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+/// Preimage revealed on withdrawal. `secret_hash` is the hashlock it opens: the escrow
+/// hashlock, or the Merkle leaf's secret hash for partial fills.
+#[cw_serde]
+pub struct RevealedSecret {
+    pub index: u32,
+    pub secret: Vec<u8>,
+    pub secret_hash: Vec<u8>,
+    pub revealed_at: Timestamp,
+}
+
+#[cw_serde]
+pub enum SwapStatus {
+    Pending,
+    Funded,
+    Completed,
+    Cancelled,
+}
+
+/// Which leg of the cross-chain swap the escrow holds.
+#[cw_serde]
+#[derive(Default)]
+pub enum EscrowMode {
+    /// Resolver funds the escrow; the maker receives on reveal, the resolver is refunded on cancel
+    #[default]
+    Destination,
+    /// Maker funds the escrow; the resolver receives on reveal, the maker is refunded on cancel
+    Source,
+}
+
+/// Token held by the escrow.
+#[cw_serde]
+pub enum Asset {
+    Native { denom: String },
+    Cw20 { contract_addr: String },
+}
+
+/// Hash function the hashlock was built with.
+#[cw_serde]
+#[derive(Default)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    /// Used by 1inch Fusion+ orders on EVM chains
+    Keccak256,
+    /// RIPEMD160(SHA256(secret)), used by Bitcoin-style HTLCs
+    Hash160,
+}
+
+impl HashAlgorithm {
+    pub fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 | HashAlgorithm::Keccak256 => 32,
+            HashAlgorithm::Hash160 => 20,
+        }
+    }
+
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+            HashAlgorithm::Keccak256 => Keccak256::digest(data).to_vec(),
+            HashAlgorithm::Hash160 => Ripemd160::digest(Sha256::digest(data)).to_vec(),
+        }
+    }
+}
+
+/// Absolute stage boundaries (unix seconds), mirroring the EVM `TimelocksLib` schedule.
+///
+/// Each field is the moment the corresponding stage starts; the finality lock runs from
+/// instantiation until `withdrawal`.
+#[cw_serde]
+pub struct Timelocks {
+    pub withdrawal: u64,
+    pub public_withdrawal: u64,
+    pub cancellation: u64,
+    pub public_cancellation: u64,
+}
+
+impl Timelocks {
+    /// Stage boundaries must be non-decreasing and cancellation must lie in the future.
+    pub fn is_valid(&self, now: u64) -> bool {
+        self.withdrawal <= self.public_withdrawal
+            && self.public_withdrawal <= self.cancellation
+            && self.cancellation <= self.public_cancellation
+            && self.cancellation > now
+    }
+
+    pub fn stage(&self, now: u64) -> TimelockStage {
+        if now >= self.public_cancellation {
+            TimelockStage::PublicCancellation
+        } else if now >= self.cancellation {
+            TimelockStage::ResolverCancellation
+        } else if now >= self.public_withdrawal {
+            TimelockStage::PublicWithdrawal
+        } else if now >= self.withdrawal {
+            TimelockStage::ResolverWithdrawal
+        } else {
+            TimelockStage::FinalityLock
+        }
+    }
+}
+
+#[cw_serde]
+pub enum TimelockStage {
+    FinalityLock,
+    ResolverWithdrawal,
+    PublicWithdrawal,
+    ResolverCancellation,
+    PublicCancellation,
+}